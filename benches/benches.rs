@@ -43,11 +43,35 @@ fn kmer2seq(c: &mut Criterion) {
                 std::hint::black_box(kmer2seq::dyn_local_buffer(std::hint::black_box(7271), k))
             })
         });
+
+        g.bench_with_input(BenchmarkId::new("caller buffer", k), &k, |b, &k| {
+            let mut buffer = [0; 32];
+
+            b.iter(|| {
+                cocktail::kmer::kmer2seq_into(std::hint::black_box(7271), k, &mut buffer);
+                std::hint::black_box(&buffer);
+            })
+        });
+    }
+
+    let kmers = (0..1 << 12).collect::<Vec<u64>>();
+    for k in (7..32).step_by(8) {
+        g.bench_with_input(BenchmarkId::new("bulk write", k), &k, |b, &k| {
+            let mut out = Vec::with_capacity(kmers.len() * (k as usize + 1));
+
+            b.iter(|| {
+                out.clear();
+                cocktail::kmer::kmers2seqs_write(std::hint::black_box(&kmers), k, &mut out)
+                    .unwrap();
+                std::hint::black_box(&out);
+            })
+        });
     }
 }
 
 mod iter_cano;
 
+#[allow(clippy::unit_arg)]
 fn tokenize_canonical(c: &mut Criterion) {
     for k in (11..19).step_by(2) {
         let mut g = c.benchmark_group(format!("canonical kmer iteration k={k}"));
@@ -76,7 +100,7 @@ fn tokenize_canonical(c: &mut Criterion) {
             g.bench_with_input(BenchmarkId::new("forward reverse", len), &seq, |b, seq| {
                 b.iter(|| {
                     std::hint::black_box(
-                        cocktail::tokenizer::kmer::Canonical::new(
+                        cocktail::tokenizer::kmer::Canonical::<u64>::new(
                             std::hint::black_box(seq),
                             std::hint::black_box(k),
                         )
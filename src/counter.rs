@@ -0,0 +1,680 @@
+//! Kmer counting tables.
+//!
+//! [DenseCounter] is a dense table indexed by [hash](crate::kmer::hash), usable for small k.
+//! For odd k table contains [get_hash_space_size](crate::kmer::get_hash_space_size) counters, a kmer and its reverse complement share the same counter.
+//! For even k table contains [get_kmer_space_size](crate::kmer::get_kmer_space_size) counters indexed by [canonical_min](crate::kmer::canonical_min).
+//!
+//! # Example
+//!
+//! ```
+//! use cocktail::counter::DenseCounter;
+//!
+//! let mut counter = DenseCounter::<u8>::new(5);
+//! counter.add_seq(b"ACTGACTGACTG");
+//!
+//! assert_eq!(counter.get(cocktail::kmer::seq2bit(b"ACTGA")), 2);
+//! assert_eq!(counter.get(cocktail::kmer::seq2bit(b"TCAGT")), 2);
+//!
+//! for (kmer, count) in counter.iter() {
+//!     // ... do what you want ...
+//! }
+//! ```
+//!
+//! [SparseCounter] store only kmers seen in a hash map, usable for any k in 1..=32.
+//! Kmers are `u64`, kmers produce by tokenizers with another [Kmer](crate::kmer::Kmer) type must be converted with [as_u64](crate::kmer::Kmer::as_u64).
+//!
+//! ```
+//! use cocktail::counter::SparseCounter;
+//! use cocktail::tokenizer::kmer::Canonical;
+//!
+//! let mut counter = SparseCounter::new(21);
+//! counter.extend(Canonical::<u64>::new(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA", 21));
+//! counter.retain_min_count(2);
+//!
+//! for (kmer, count) in counter.sorted() {
+//!     // ... do what you want ...
+//! }
+//! ```
+
+/* standard use */
+use std::collections::HashMap;
+use std::hash::{BuildHasher as _, BuildHasherDefault};
+
+/* crates use */
+
+/* project use */
+use crate::error;
+use crate::kmer;
+use crate::tokenizer;
+use crate::tokenizer::minimizer::method;
+
+/* module declaration */
+pub mod disk;
+pub mod format;
+
+/// Largest kmer size supported by [DenseCounter], table of k = 16 contains 2^32 counters
+pub const MAX_K: u8 = 16;
+
+/// An unsigned integer used as saturating counter.
+///
+/// Implemented for `u8`, `u16` and `u32`.
+pub trait Count: Copy + Default + std::fmt::Debug + Eq + Ord {
+    /// Largest count
+    const MAX: Self;
+
+    /// Number of bytes of counter
+    const WIDTH: u8;
+
+    /// Convert count in u32
+    fn to_u32(self) -> u32;
+
+    /// Convert a u32 in count, value larger than [MAX](Count::MAX) are saturated
+    fn from_u32(count: u32) -> Self;
+
+    /// Add one without overflow
+    fn increment(self) -> Self;
+
+    /// Add other without overflow
+    fn saturating_add(self, other: Self) -> Self;
+}
+
+macro_rules! impl_count {
+    ($($t:ty),*) => {
+        $(
+            impl Count for $t {
+                const MAX: Self = <$t>::MAX;
+
+                const WIDTH: u8 = std::mem::size_of::<$t>() as u8;
+
+                #[inline(always)]
+                fn to_u32(self) -> u32 {
+                    self as u32
+                }
+
+                #[inline(always)]
+                fn from_u32(count: u32) -> Self {
+                    <$t>::try_from(count).unwrap_or(<$t>::MAX)
+                }
+
+                #[inline(always)]
+                fn increment(self) -> Self {
+                    <$t>::saturating_add(self, 1)
+                }
+
+                #[inline(always)]
+                fn saturating_add(self, other: Self) -> Self {
+                    <$t>::saturating_add(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_count!(u8, u16, u32);
+
+/// A table that store a count for each canonical kmer of size k
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DenseCounter<C> {
+    k: u8,
+    counts: Box<[C]>,
+}
+
+impl<C> DenseCounter<C>
+where
+    C: Count,
+{
+    /// Create a new DenseCounter for kmer of size k, all counts are 0
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=[MAX_K], see [try_new](DenseCounter::try_new)
+    pub fn new(k: u8) -> Self {
+        Self::try_new(k).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](DenseCounter::new) but return an error if k isn't in 1..=[MAX_K]
+    pub fn try_new(k: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, MAX_K)?;
+
+        Ok(DenseCounter {
+            k,
+            counts: vec![C::default(); dense_size(k) as usize].into_boxed_slice(),
+        })
+    }
+
+    /// Return kmer size
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+
+    /// Return the number of counters in table
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Return true if table contains no counter
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Increment count of kmer, kmer can be in any orientation
+    #[inline(always)]
+    pub fn add(&mut self, kmer: u64) {
+        let index = self.index(kmer);
+        self.counts[index] = self.counts[index].increment();
+    }
+
+    /// Increment count of each kmer of seq, kmers are produce by [Canonical](tokenizer::kmer::Canonical)
+    pub fn add_seq(&mut self, seq: &[u8]) {
+        for cano in tokenizer::kmer::Canonical::<u64>::new(seq, self.k) {
+            let index = self.hash(cano);
+            self.counts[index] = self.counts[index].increment();
+        }
+    }
+
+    /// Return count of kmer, kmer can be in any orientation
+    #[inline(always)]
+    pub fn get(&self, kmer: u64) -> C {
+        self.counts[self.index(kmer)]
+    }
+
+    /// Add counts of other in self
+    ///
+    /// # Panics
+    ///
+    /// If other kmer size isn't equal to self kmer size
+    pub fn merge(&mut self, other: &Self) {
+        assert_eq!(
+            self.k, other.k,
+            "can't merge DenseCounter with different kmer size"
+        );
+
+        for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count = count.saturating_add(*other);
+        }
+    }
+
+    /// Return an iterator on canonical kmer and count, kmer with a count of 0 are skipped
+    pub fn iter(&self) -> impl Iterator<Item = (u64, C)> + '_ {
+        let k = self.k;
+
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count != C::default())
+            .map(move |(hash, count)| (kmer::hash2kmer(hash as u64, k), *count))
+    }
+
+    /// Return table index of a canonical kmer
+    #[inline(always)]
+    fn hash(&self, cano: u64) -> usize {
+        if self.k % 2 == 1 {
+            kmer::remove_first_bit(cano) as usize
+        } else {
+            cano as usize
+        }
+    }
+
+    /// Return table index of a kmer in any orientation
+    #[inline(always)]
+    fn index(&self, kmer: u64) -> usize {
+        self.hash(kmer::canonical_any(kmer, self.k))
+    }
+}
+
+/// Number of counters of a dense table of kmer size k, one by [hash](kmer::hash) for odd k and one by [canonical_min](kmer::canonical_min) for even k
+pub(crate) fn dense_size(k: u8) -> u64 {
+    if k % 2 == 1 {
+        kmer::get_hash_space_size(k)
+    } else {
+        kmer::get_kmer_space_size(k)
+    }
+}
+
+/// A hasher for 2 bit kmer, much faster than SipHash on `u64` key, it isn't resistant to HashDoS
+///
+/// Kmers are combined by rotation and xor, result is mixed with [mix](kmer::mix).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KmerHasher {
+    hash: u64,
+}
+
+impl std::hash::Hasher for KmerHasher {
+    #[inline(always)]
+    fn finish(&self) -> u64 {
+        kmer::mix(self.hash)
+    }
+
+    #[inline(always)]
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.write_u64(*byte as u64);
+        }
+    }
+
+    #[inline(always)]
+    fn write_u64(&mut self, kmer: u64) {
+        self.hash = self.hash.rotate_left(5) ^ kmer;
+    }
+}
+
+/// [BuildHasher](std::hash::BuildHasher) of [KmerHasher]
+pub type KmerBuildHasher = BuildHasherDefault<KmerHasher>;
+
+/// A table that store a count for each canonical kmer of size k seen, kmers are `u64` so k must be lower or equal to 32
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SparseCounter {
+    k: u8,
+    counts: HashMap<u64, u32, KmerBuildHasher>,
+}
+
+impl SparseCounter {
+    /// Create a new empty SparseCounter for kmer of size k
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32, see [try_new](SparseCounter::try_new)
+    pub fn new(k: u8) -> Self {
+        Self::try_new(k).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](SparseCounter::new) but return an error if k isn't in 1..=32
+    pub fn try_new(k: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, 32)?;
+
+        Ok(SparseCounter {
+            k,
+            counts: HashMap::default(),
+        })
+    }
+
+    /// Return kmer size
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+
+    /// Return the number of distinct canonical kmers
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Return true if no kmer is count
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Increment count of kmer, kmer can be in any orientation
+    #[inline(always)]
+    pub fn add(&mut self, kmer: u64) {
+        let count = self.counts.entry(self.canonical(kmer)).or_default();
+        *count = count.saturating_add(1);
+    }
+
+    /// Return count of kmer, kmer can be in any orientation
+    pub fn get(&self, kmer: u64) -> u32 {
+        self.counts
+            .get(&self.canonical(kmer))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Add counts of other in self
+    ///
+    /// # Panics
+    ///
+    /// If other kmer size isn't equal to self kmer size
+    pub fn merge(&mut self, other: &Self) {
+        assert_eq!(
+            self.k, other.k,
+            "can't merge SparseCounter with different kmer size"
+        );
+
+        for (kmer, other) in other.counts.iter() {
+            let count = self.counts.entry(*kmer).or_default();
+            *count = count.saturating_add(*other);
+        }
+    }
+
+    /// Remove kmers with a count lower than min_count
+    pub fn retain_min_count(&mut self, min_count: u32) {
+        self.counts.retain(|_, count| *count >= min_count);
+    }
+
+    /// Return canonical kmers and count sorted by kmer
+    pub fn sorted(&self) -> Vec<(u64, u32)> {
+        let mut kmers = self
+            .counts
+            .iter()
+            .map(|(kmer, count)| (*kmer, *count))
+            .collect::<Vec<(u64, u32)>>();
+        kmers.sort_unstable();
+
+        kmers
+    }
+
+    /// Return an iterator on canonical kmers and count in arbitrary order
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u32)> + '_ {
+        self.counts.iter().map(|(kmer, count)| (*kmer, *count))
+    }
+
+    #[inline(always)]
+    fn canonical(&self, kmer: u64) -> u64 {
+        kmer::canonical_any(kmer, self.k)
+    }
+}
+
+impl Extend<u64> for SparseCounter {
+    fn extend<I>(&mut self, kmers: I)
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        for kmer in kmers {
+            self.add(kmer);
+        }
+    }
+}
+
+/// Count canonical kmers of seqs on threads, result is the same as a [SparseCounter] feed with [Canonical](tokenizer::kmer::Canonical) on each sequence.
+///
+/// Each kmer is assigned to one of shards with its minimizer, see [minimizer::Canonical](tokenizer::minimizer::Canonical), so all kmers of a super-kmer go to the same shard.
+/// Each thread count kmers of a part of seqs in its own shards, without lock, then shards with same index are merged in parallel.
+///
+/// # Panics
+///
+/// If k isn't in 1..=32 or m isn't in 1..=k
+///
+/// # Example
+///
+/// ```
+/// use cocktail::counter;
+/// use cocktail::tokenizer::minimizer::method;
+///
+/// let reads = vec![b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAG".to_vec(), b"AAAGGTGCGTGTTTCCGAGA".to_vec()];
+///
+/// let counts = counter::count_parallel::<method::Random, _>(&reads, 15, 7, 4, 64);
+/// ```
+pub fn count_parallel<M, S>(
+    seqs: &[S],
+    k: u8,
+    m: u8,
+    threads: usize,
+    shards: usize,
+) -> SparseCounter
+where
+    M: method::Method<u64>,
+    S: AsRef<[u8]> + Sync,
+{
+    kmer::check_kmer_size(k, 32).unwrap_or_else(|e| panic!("{}", e));
+    kmer::check_minimizer_size(k, m).unwrap_or_else(|e| panic!("{}", e));
+
+    let threads = threads.max(1);
+    let shards = shards.max(1);
+
+    // Count: each thread own its shards
+    let mut by_shard: Vec<Vec<SparseCounter>> = (0..shards).map(|_| Vec::new()).collect();
+    std::thread::scope(|scope| {
+        let handles = (0..threads)
+            .map(|thread| {
+                scope.spawn(move || {
+                    let mut local = vec![SparseCounter::new(k); shards];
+
+                    for seq in seqs.iter().skip(thread).step_by(threads) {
+                        for (kmer, minimizer) in
+                            tokenizer::minimizer::Canonical::<M, u64>::new(seq.as_ref(), k, m)
+                        {
+                            let shard = KmerBuildHasher::default().hash_one(minimizer) as usize;
+                            local[shard % shards].add(kmer);
+                        }
+                    }
+
+                    local
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            let local = handle
+                .join()
+                .unwrap_or_else(|e| std::panic::resume_unwind(e));
+
+            for (shard, counter) in local.into_iter().enumerate() {
+                by_shard[shard].push(counter);
+            }
+        }
+    });
+
+    // Merge: shards are disjoint so each one can be merged independently
+    let mut merged: Vec<SparseCounter> = Vec::with_capacity(shards);
+    std::thread::scope(|scope| {
+        let by_thread = shards.div_ceil(threads);
+        let mut handles = Vec::new();
+
+        while !by_shard.is_empty() {
+            let part = by_shard.split_off(by_shard.len().saturating_sub(by_thread));
+            handles.push(scope.spawn(move || {
+                part.into_iter()
+                    .map(|counters| {
+                        counters
+                            .into_iter()
+                            .reduce(|mut acc, counter| {
+                                acc.merge(&counter);
+                                acc
+                            })
+                            .unwrap_or_else(|| SparseCounter::new(k))
+                    })
+                    .collect::<Vec<SparseCounter>>()
+            }));
+        }
+
+        for handle in handles {
+            merged.extend(
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e)),
+            );
+        }
+    });
+
+    let mut result = SparseCounter::new(k);
+    result
+        .counts
+        .reserve(merged.iter().map(SparseCounter::len).sum());
+    for shard in merged {
+        result.counts.extend(shard.counts);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count() {
+        let mut counter = DenseCounter::<u8>::new(5);
+        assert_eq!(counter.len(), 512);
+
+        counter.add_seq(b"ACTGACTGACTG");
+        counter.add(kmer::seq2bit(b"ACTGA"));
+
+        assert_eq!(counter.get(kmer::seq2bit(b"ACTGA")), 3);
+        assert_eq!(counter.get(kmer::seq2bit(b"TCAGT")), 3);
+        assert_eq!(counter.get(kmer::seq2bit(b"CTGAC")), 2);
+        assert_eq!(counter.get(kmer::seq2bit(b"AAAAA")), 0);
+    }
+
+    #[test]
+    fn even_k() {
+        let mut counter = DenseCounter::<u16>::new(4);
+        assert_eq!(counter.len(), 256);
+
+        counter.add_seq(b"ACGTACGT");
+
+        // ACGT is its own reverse complement
+        assert_eq!(counter.get(kmer::seq2bit(b"ACGT")), 2);
+        assert_eq!(counter.get(kmer::seq2bit(b"CGTA")), 2);
+        assert_eq!(counter.get(kmer::seq2bit(b"TACG")), 2);
+    }
+
+    #[test]
+    fn saturate() {
+        let mut counter = DenseCounter::<u8>::new(3);
+
+        for _ in 0..300 {
+            counter.add(kmer::seq2bit(b"ACT"));
+        }
+        assert_eq!(counter.get(kmer::seq2bit(b"ACT")), u8::MAX);
+
+        let other = counter.clone();
+        counter.merge(&other);
+        assert_eq!(counter.get(kmer::seq2bit(b"AGT")), u8::MAX);
+    }
+
+    #[test]
+    fn merge() {
+        let mut first = DenseCounter::<u32>::new(7);
+        first.add_seq(b"ACTGACTGAC");
+
+        let mut second = DenseCounter::<u32>::new(7);
+        second.add_seq(b"GTCAGTCAGT");
+
+        first.merge(&second);
+
+        let mut all = DenseCounter::<u32>::new(7);
+        all.add_seq(b"ACTGACTGAC");
+        all.add_seq(b"GTCAGTCAGT");
+
+        assert_eq!(first, all);
+    }
+
+    #[test]
+    fn iter() {
+        let mut counter = DenseCounter::<u8>::new(5);
+        counter.add_seq(b"ACTGACTG");
+        counter.add_seq(b"ACTGA");
+
+        let mut expected = crate::tokenizer::kmer::Canonical::<u64>::new(b"ACTGACTG", 5)
+            .map(|kmer| {
+                (
+                    kmer,
+                    if kmer == kmer::canonical(kmer::seq2bit(b"ACTGA"), 5) {
+                        2
+                    } else {
+                        1
+                    },
+                )
+            })
+            .collect::<Vec<(u64, u8)>>();
+        expected.sort();
+
+        let mut kmers = counter.iter().collect::<Vec<(u64, u8)>>();
+        kmers.sort();
+
+        assert_eq!(kmers, expected);
+    }
+
+    #[test]
+    fn invalid_size() {
+        assert_eq!(
+            DenseCounter::<u8>::try_new(17).err(),
+            Some(error::Error::KmerSize { k: 17, max: 16 })
+        );
+    }
+
+    #[test]
+    fn sparse() {
+        let mut counter = SparseCounter::new(5);
+        counter.extend(crate::tokenizer::kmer::Forward::<u64>::new(
+            b"ACTGACTGACTG",
+            5,
+        ));
+        counter.add(kmer::seq2bit(b"TCAGT"));
+
+        assert_eq!(counter.len(), 4);
+        assert_eq!(counter.get(kmer::seq2bit(b"ACTGA")), 3);
+        assert_eq!(counter.get(kmer::seq2bit(b"CTGAC")), 2);
+        assert_eq!(counter.get(kmer::seq2bit(b"AAAAA")), 0);
+
+        let mut dense = DenseCounter::<u8>::new(5);
+        dense.add_seq(b"ACTGACTGACTG");
+        dense.add(kmer::seq2bit(b"TCAGT"));
+
+        assert_eq!(
+            counter.sorted(),
+            dense
+                .iter()
+                .map(|(kmer, count)| (kmer, count as u32))
+                .collect::<Vec<(u64, u32)>>()
+        );
+    }
+
+    #[test]
+    fn sparse_large_k() {
+        let seq = b"ACTGACTGGATCAATGGACACGTACGATCGATCAG".repeat(2);
+
+        let mut counter = SparseCounter::new(31);
+        counter.extend(crate::tokenizer::kmer::Canonical::<u64>::new(&seq, 31));
+
+        let kmers = counter.sorted();
+        assert!(kmers.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(
+            kmers.iter().map(|(_, c)| *c as usize).sum::<usize>(),
+            seq.len() - 31 + 1
+        );
+
+        counter.retain_min_count(2);
+        assert!(counter.iter().all(|(_, count)| count >= 2));
+        assert_eq!(counter.len(), 5);
+    }
+
+    #[test]
+    fn sparse_merge() {
+        let mut first = SparseCounter::new(6);
+        first.extend(crate::tokenizer::kmer::Canonical::<u64>::new(
+            b"ACTGACTGAC",
+            6,
+        ));
+
+        let mut second = SparseCounter::new(6);
+        second.extend(crate::tokenizer::kmer::Canonical::<u64>::new(
+            b"GTCAGTCAGT",
+            6,
+        ));
+
+        first.merge(&second);
+
+        let mut all = SparseCounter::new(6);
+        all.extend(crate::tokenizer::kmer::Canonical::<u64>::new(
+            b"ACTGACTGAC",
+            6,
+        ));
+        all.extend(crate::tokenizer::kmer::Canonical::<u64>::new(
+            b"GTCAGTCAGT",
+            6,
+        ));
+
+        assert_eq!(first.sorted(), all.sorted());
+    }
+
+    #[test]
+    fn parallel() {
+        let mut reads = crate::test_utils::random_reads(20, 150);
+        // add duplicate to get count larger than 1
+        reads.extend_from_within(0..5);
+
+        for (k, m) in [(15, 7), (16, 8), (31, 11)] {
+            let mut single = SparseCounter::new(k);
+            for read in reads.iter() {
+                single.extend(crate::tokenizer::kmer::Canonical::<u64>::new(read, k));
+            }
+
+            for (threads, shards) in [(1, 1), (3, 16), (8, 5)] {
+                assert_eq!(
+                    count_parallel::<method::Random, _>(&reads, k, m, threads, shards),
+                    single
+                );
+            }
+        }
+    }
+}
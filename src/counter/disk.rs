@@ -0,0 +1,545 @@
+//! Count kmers with external memory, in two pass.
+//!
+//! Pass one split each sequence in super-kmers (consecutive kmers with the same minimizer, see [minimizer::Canonical](crate::tokenizer::minimizer::Canonical)) and write them, in 2 bit representation, in bucket files selected by minimizer.
+//! Pass two count kmers of each bucket in memory, if a bucket is too large for memory budget its kmers are split by hash and bucket is read multiple time.
+//! Counts of each part are sorted and write in run files, runs are merged on the fly by [Counts] iterator.
+//!
+//! Memory budget is approximate, it's used to size write buffers in pass one and to limit the number of kmers count in the same time in pass two.
+//! If budget can't hold a write buffer of 4 KiB by bucket, number of buckets is reduced.
+//!
+//! Each DiskCounter work in its own subdirectory, it's removed when DiskCounter, or [Counts] build from it, is drop.
+//!
+//! # Example
+//!
+//! ```
+//! use cocktail::counter::disk::DiskCounter;
+//! use cocktail::tokenizer::minimizer::method;
+//!
+//! let mut counter = DiskCounter::<method::Random>::new(std::env::temp_dir(), 15, 7, 16, 1 << 20).unwrap();
+//! counter.add_seq(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA").unwrap();
+//!
+//! for result in counter.count(1).unwrap() {
+//!     let (kmer, count) = result.unwrap();
+//!     // ... do what you want ...
+//! }
+//! ```
+
+/* standard use */
+use std::collections::BinaryHeap;
+use std::hash::BuildHasher as _;
+use std::io::{BufRead as _, Read as _, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/* crates use */
+
+/* project use */
+use crate::counter::{KmerBuildHasher, SparseCounter};
+use crate::error;
+use crate::kmer;
+use crate::tokenizer;
+use crate::tokenizer::minimizer::method;
+
+/// Approximate number of bytes used in memory to count one kmer, an entry of hash map and its copy in sorted vector
+const BYTES_BY_KMER: usize = 64;
+
+/// Size of one entry in run file, kmer (u64) and count (u32)
+const RUN_ENTRY: usize = 12;
+
+/// Smallest write buffer of a bucket
+const MIN_BUFFER: usize = 1 << 12;
+
+/// Largest number of run files merged in the same time
+const MAX_MERGED_RUNS: usize = 64;
+
+/// Number of DiskCounter create by this process, used to build unique directory name
+static INSTANCES: AtomicUsize = AtomicUsize::new(0);
+
+/// A kmer counter that store super-kmers on disk before count them
+pub struct DiskCounter<M> {
+    k: u8,
+    m: u8,
+    memory: usize,
+    buckets: Vec<std::io::BufWriter<std::fs::File>>,
+    sizes: Vec<usize>,
+    packed: Vec<u8>,
+    method: std::marker::PhantomData<M>,
+    dir: WorkDir,
+}
+
+impl<M> DiskCounter<M>
+where
+    M: method::Method<u64>,
+{
+    /// Create a new DiskCounter, kmer size is k, minimizer size is m, bucket files are create in a new subdirectory of dir, memory is the memory budget in bytes
+    ///
+    /// Return an error if k isn't in 1..=32, m isn't in 1..=k or if subdirectory or bucket files can't be create
+    pub fn new<P>(dir: P, k: u8, m: u8, buckets: usize, memory: usize) -> Result<Self, error::Error>
+    where
+        P: AsRef<Path>,
+    {
+        kmer::check_kmer_size(k, 32)?;
+        kmer::check_minimizer_size(k, m)?;
+
+        let dir = WorkDir::new(dir.as_ref())?;
+
+        // half of budget is used by write buffers
+        let buckets = buckets.min(memory / 2 / MIN_BUFFER).max(1);
+        let buffer_size = (memory / 2 / buckets).max(MIN_BUFFER);
+
+        let files = (0..buckets)
+            .map(|i| {
+                std::fs::File::create(dir.join(format!("bucket_{}.bin", i)))
+                    .map(|file| std::io::BufWriter::with_capacity(buffer_size, file))
+            })
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+
+        Ok(DiskCounter {
+            k,
+            m,
+            memory,
+            buckets: files,
+            sizes: vec![0; buckets],
+            packed: Vec::new(),
+            method: std::marker::PhantomData,
+            dir,
+        })
+    }
+
+    /// Pass one: split seq in super-kmers and write them in bucket files
+    ///
+    /// Return an error if seq is longer than `u32::MAX`
+    pub fn add_seq(&mut self, seq: &[u8]) -> Result<(), error::Error> {
+        if seq.len() > u32::MAX as usize {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "sequence longer than u32::MAX can't be store in bucket",
+            )
+            .into());
+        }
+
+        let k = self.k as usize;
+
+        let mut start = 0;
+        let mut current = None;
+        for (i, (_, minimizer)) in
+            tokenizer::minimizer::Canonical::<M, u64>::new(seq, self.k, self.m).enumerate()
+        {
+            match current {
+                Some(previous) if previous == minimizer => (),
+                Some(previous) => {
+                    self.write_superkmer(previous, &seq[start..i + k - 1])?;
+                    start = i;
+                    current = Some(minimizer);
+                }
+                None => current = Some(minimizer),
+            }
+        }
+
+        if let Some(previous) = current {
+            self.write_superkmer(previous, &seq[start..])?;
+        }
+
+        Ok(())
+    }
+
+    /// Pass two: count kmers of each bucket, kmers with a count lower than min_count are removed
+    ///
+    /// Bucket files are removed, run files are merged by batch of at most 64 files, last runs are removed when [Counts] is drop.
+    pub fn count(mut self, min_count: u32) -> Result<Counts, error::Error> {
+        for bucket in self.buckets.iter_mut() {
+            bucket.flush()?;
+        }
+        self.buckets.clear();
+
+        let budget = (self.memory / BYTES_BY_KMER).max(1);
+        let mut runs = Vec::new();
+
+        for (i, size) in self.sizes.iter().enumerate() {
+            let path = self.dir.join(format!("bucket_{}.bin", i));
+
+            // number of nucleotides is an upper bound of number of kmers
+            let parts = size.div_ceil(budget).max(1);
+            for part in 0..parts {
+                let mut counter = SparseCounter::new(self.k);
+
+                let mut input = std::io::BufReader::new(std::fs::File::open(&path)?);
+                let mut seq = Vec::new();
+                while read_superkmer(&mut input, &mut seq)? {
+                    for kmer in tokenizer::kmer::Canonical::<u64>::new(&seq, self.k) {
+                        if parts == 1
+                            || KmerBuildHasher::default().hash_one(kmer) as usize % parts == part
+                        {
+                            counter.add(kmer);
+                        }
+                    }
+                }
+
+                counter.retain_min_count(min_count);
+
+                let run = self.dir.join(format!("run_{}_{}.bin", i, part));
+                let mut output = std::io::BufWriter::new(std::fs::File::create(&run)?);
+                for (kmer, count) in counter.sorted() {
+                    write_entry(&mut output, kmer, count)?;
+                }
+                output.flush()?;
+                runs.push(run);
+            }
+
+            std::fs::remove_file(&path)?;
+        }
+
+        // limit number of open files, merge runs until they can be merged in the same time
+        let dir = Arc::new(self.dir);
+        let mut merged = 0;
+        while runs.len() > MAX_MERGED_RUNS {
+            let mut next = Vec::new();
+            for batch in runs.chunks(MAX_MERGED_RUNS) {
+                let run = dir.join(format!("merge_{}.bin", merged));
+                merged += 1;
+
+                let mut output = std::io::BufWriter::new(std::fs::File::create(&run)?);
+                for result in Counts::new(batch.to_vec(), dir.clone())? {
+                    let (kmer, count) = result?;
+                    write_entry(&mut output, kmer, count)?;
+                }
+                output.flush()?;
+                next.push(run);
+            }
+            runs = next;
+        }
+
+        Counts::new(runs, dir)
+    }
+
+    fn write_superkmer(&mut self, minimizer: u64, seq: &[u8]) -> Result<(), error::Error> {
+        let bucket = KmerBuildHasher::default().hash_one(minimizer) as usize % self.buckets.len();
+
+        self.packed.clear();
+        for chunk in seq.chunks(4) {
+            let byte = chunk
+                .iter()
+                .fold(0u8, |acc, nuc| (acc << 2) | kmer::nuc2bit(*nuc) as u8);
+            self.packed.push(byte << (2 * (4 - chunk.len())));
+        }
+
+        let output = &mut self.buckets[bucket];
+        output.write_all(&(seq.len() as u32).to_le_bytes())?;
+        output.write_all(&self.packed)?;
+        self.sizes[bucket] += seq.len();
+
+        Ok(())
+    }
+}
+
+/// Write a kmer and its count in a run file
+fn write_entry<W>(output: &mut W, kmer: u64, count: u32) -> Result<(), error::Error>
+where
+    W: std::io::Write,
+{
+    output.write_all(&kmer.to_le_bytes())?;
+    output.write_all(&count.to_le_bytes())?;
+
+    Ok(())
+}
+
+/// Read a super-kmer write by [DiskCounter::add_seq] in seq, return false at end of file
+fn read_superkmer<R>(input: &mut R, seq: &mut Vec<u8>) -> Result<bool, error::Error>
+where
+    R: std::io::BufRead,
+{
+    if input.fill_buf()?.is_empty() {
+        return Ok(false);
+    }
+
+    let mut len = [0; 4];
+    input.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+
+    let mut packed = vec![0; len.div_ceil(4)];
+    input.read_exact(&mut packed)?;
+
+    seq.clear();
+    for i in 0..len {
+        let bit = (packed[i / 4] >> (6 - 2 * (i % 4))) & 0b11;
+        seq.push(kmer::bit2nuc(bit as u64));
+    }
+
+    Ok(true)
+}
+
+/// An iterator that merge run files and produce canonical kmers and count in sorted order
+pub struct Counts {
+    runs: Vec<PathBuf>,
+    readers: Vec<std::io::BufReader<std::fs::File>>,
+    heap: BinaryHeap<std::cmp::Reverse<(u64, u32, usize)>>,
+    _dir: Arc<WorkDir>,
+}
+
+impl Counts {
+    fn new(runs: Vec<PathBuf>, dir: Arc<WorkDir>) -> Result<Self, error::Error> {
+        let mut counts = Counts {
+            readers: runs
+                .iter()
+                .map(|run| std::fs::File::open(run).map(std::io::BufReader::new))
+                .collect::<Result<Vec<_>, std::io::Error>>()?,
+            runs,
+            heap: BinaryHeap::new(),
+            _dir: dir,
+        };
+
+        for i in 0..counts.readers.len() {
+            counts.push_next(i)?;
+        }
+
+        Ok(counts)
+    }
+
+    fn push_next(&mut self, run: usize) -> Result<(), error::Error> {
+        let reader = &mut self.readers[run];
+        if reader.fill_buf()?.is_empty() {
+            return Ok(());
+        }
+
+        let mut entry = [0; RUN_ENTRY];
+        reader.read_exact(&mut entry)?;
+
+        let kmer = u64::from_le_bytes(entry[..8].try_into().unwrap_or_default());
+        let count = u32::from_le_bytes(entry[8..].try_into().unwrap_or_default());
+        self.heap.push(std::cmp::Reverse((kmer, count, run)));
+
+        Ok(())
+    }
+}
+
+impl Iterator for Counts {
+    type Item = Result<(u64, u32), error::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let std::cmp::Reverse((kmer, count, run)) = self.heap.pop()?;
+
+        if let Err(e) = self.push_next(run) {
+            return Some(Err(e));
+        }
+
+        Some(Ok((kmer, count)))
+    }
+}
+
+impl Drop for Counts {
+    fn drop(&mut self) {
+        for run in self.runs.iter() {
+            let _ = std::fs::remove_file(run);
+        }
+    }
+}
+
+/// A directory own by one DiskCounter, it's removed with its content when drop
+struct WorkDir(PathBuf);
+
+impl WorkDir {
+    fn new(parent: &Path) -> Result<Self, error::Error> {
+        loop {
+            let path = parent.join(format!(
+                "cocktail_disk_{}_{}",
+                std::process::id(),
+                INSTANCES.fetch_add(1, Ordering::Relaxed)
+            ));
+
+            match std::fs::create_dir(&path) {
+                Ok(()) => return Ok(WorkDir(path)),
+                // a previous process with same pid could have left a directory
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn join(&self, name: String) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /* project use */
+    use super::*;
+    use crate::test_utils::random_reads;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("cocktail_test_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    fn reads() -> Vec<Vec<u8>> {
+        let mut reads = random_reads(20, 150);
+        reads.extend_from_within(0..5);
+
+        reads
+    }
+
+    fn expected(reads: &[Vec<u8>], k: u8, min_count: u32) -> Vec<(u64, u32)> {
+        let mut counter = SparseCounter::new(k);
+        for read in reads {
+            counter.extend(tokenizer::kmer::Canonical::<u64>::new(read, k));
+        }
+        counter.retain_min_count(min_count);
+
+        counter.sorted()
+    }
+
+    #[test]
+    fn count() {
+        let dir = temp_dir("disk_count");
+        let reads = reads();
+
+        for (k, m) in [(15, 7), (16, 8), (31, 11)] {
+            let mut counter = DiskCounter::<method::Random>::new(&dir, k, m, 8, 1 << 20).unwrap();
+            for read in reads.iter() {
+                counter.add_seq(read).unwrap();
+            }
+
+            assert_eq!(
+                counter
+                    .count(1)
+                    .unwrap()
+                    .collect::<Result<Vec<(u64, u32)>, error::Error>>()
+                    .unwrap(),
+                expected(&reads, k, 1)
+            );
+        }
+
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn small_budget() {
+        let dir = temp_dir("disk_small_budget");
+        let reads = reads();
+
+        // budget of 100 kmers force to split each bucket in multiple part
+        let mut counter =
+            DiskCounter::<method::Random>::new(&dir, 21, 9, 3, 100 * BYTES_BY_KMER).unwrap();
+        for read in reads.iter() {
+            counter.add_seq(read).unwrap();
+        }
+        counter.add_seq(b"ACTG").unwrap();
+
+        assert_eq!(
+            counter
+                .count(2)
+                .unwrap()
+                .collect::<Result<Vec<(u64, u32)>, error::Error>>()
+                .unwrap(),
+            expected(&reads, 21, 2)
+        );
+
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn batched_merge() {
+        let dir = temp_dir("disk_batched_merge");
+        let reads = reads();
+
+        // budget of 10 kmers produce more runs than MAX_MERGED_RUNS
+        let mut counter =
+            DiskCounter::<method::Random>::new(&dir, 15, 7, 4, 10 * BYTES_BY_KMER).unwrap();
+        for read in reads.iter() {
+            counter.add_seq(read).unwrap();
+        }
+        assert!(counter.sizes.iter().sum::<usize>() / 10 > MAX_MERGED_RUNS);
+
+        assert_eq!(
+            counter
+                .count(1)
+                .unwrap()
+                .collect::<Result<Vec<(u64, u32)>, error::Error>>()
+                .unwrap(),
+            expected(&reads, 15, 1)
+        );
+
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn own_directory() {
+        let dir = temp_dir("disk_own_directory");
+        let reads = reads();
+
+        let mut first = DiskCounter::<method::Random>::new(&dir, 15, 7, 4, 1 << 20).unwrap();
+        let mut second = DiskCounter::<method::Random>::new(&dir, 15, 7, 4, 1 << 20).unwrap();
+        assert_ne!(first.dir.0, second.dir.0);
+        for read in reads.iter() {
+            first.add_seq(read).unwrap();
+            second.add_seq(read).unwrap();
+        }
+
+        let first = first.count(1).unwrap();
+        let second = second.count(2).unwrap();
+        assert_eq!(
+            first
+                .collect::<Result<Vec<(u64, u32)>, error::Error>>()
+                .unwrap(),
+            expected(&reads, 15, 1)
+        );
+        assert_eq!(
+            second
+                .collect::<Result<Vec<(u64, u32)>, error::Error>>()
+                .unwrap(),
+            expected(&reads, 15, 2)
+        );
+
+        // counter drop without count remove its directory
+        let mut counter = DiskCounter::<method::Random>::new(&dir, 15, 7, 4, 1 << 20).unwrap();
+        counter.add_seq(&reads[0]).unwrap();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        drop(counter);
+
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn memory_budget() {
+        let dir = temp_dir("disk_memory_budget");
+
+        let counter = DiskCounter::<method::Random>::new(&dir, 15, 7, 64, 1 << 16).unwrap();
+        assert_eq!(counter.buckets.len(), 8);
+        assert_eq!(counter.buckets[0].capacity(), MIN_BUFFER);
+
+        let counter = DiskCounter::<method::Random>::new(&dir, 15, 7, 64, 0).unwrap();
+        assert_eq!(counter.buckets.len(), 1);
+        drop(counter);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn superkmer() {
+        let mut packed = Vec::new();
+        packed.extend(5u32.to_le_bytes());
+        packed.push(0b00_01_10_11);
+        packed.push(0b01_00_00_00);
+
+        let mut input = &packed[..];
+        let mut seq = Vec::new();
+
+        assert!(read_superkmer(&mut input, &mut seq).unwrap());
+        assert_eq!(seq, b"ACTGC");
+        assert!(!read_superkmer(&mut input, &mut seq).unwrap());
+    }
+}
@@ -0,0 +1,776 @@
+//! Read and write kmer count tables in a versioned binary format.
+//!
+//! All integers are little endian. A file is made of a header, a body and a footer:
+//!
+//! | field           | size | value                                                       |
+//! |-----------------|------|-------------------------------------------------------------|
+//! | signature       | 4    | `CKTC`                                                      |
+//! | version         | 1    | [VERSION]                                                   |
+//! | k               | 1    | kmer size                                                   |
+//! | encoding        | 1    | 0 = cocktail 2 bit encoding (A=00, C=01, T=10, G=11)        |
+//! | mode            | 1    | 0 = canonical kmer, 1 = [hash](crate::kmer::hash2kmer)      |
+//! | body            | 1    | 0 = dense, 1 = sparse                                       |
+//! | width           | 1    | number of bytes of a counter, 1, 2 or 4                     |
+//! | reserved        | 2    | 0                                                           |
+//! | entries         | 8    | number of entries in body                                   |
+//! | header checksum | 8    | FNV-1a of previous header bytes                             |
+//!
+//! Dense body contains a counter for each hash, hash is the index of counter, so mode is always hash.
+//! Sparse body contains kmer (or hash) on 8 bytes and its counter, sorted by increasing kmer.
+//!
+//! Footer contains FNV-1a of body bytes.
+//!
+//! # Example
+//!
+//! ```
+//! use cocktail::counter::format;
+//! use cocktail::counter::SparseCounter;
+//! use cocktail::tokenizer::kmer::Canonical;
+//!
+//! let mut counter = SparseCounter::new(21);
+//! counter.extend(Canonical::<u64>::new(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA", 21));
+//!
+//! let mut file = Vec::new();
+//! format::write_sparse(&mut file, &counter).unwrap();
+//!
+//! let reader = format::Reader::with_k(&file[..], 21).unwrap();
+//! for result in reader {
+//!     let (kmer, count) = result.unwrap();
+//!     // ... do what you want ...
+//! }
+//! ```
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+use crate::counter::{dense_size, Count, DenseCounter, SparseCounter};
+use crate::error;
+use crate::kmer;
+
+/// Version of format write by [Writer]
+pub const VERSION: u8 = 1;
+
+const SIGNATURE: [u8; 4] = *b"CKTC";
+
+const HEADER_SIZE: usize = 28;
+
+const TRUNCATED: error::Error = error::Error::CountTableFormat {
+    reason: "unexpected end of file",
+};
+
+/// How kmers are store in file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Canonical kmer, see [canonical](crate::kmer::canonical) for odd k and [canonical_min](crate::kmer::canonical_min) for even k
+    Canonical,
+
+    /// Canonical kmer without the rightest bit for odd k, see [hash](crate::kmer::hash), canonical kmer for even k
+    Hash,
+}
+
+/// Layout of file body
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Body {
+    /// A counter for each hash
+    Dense,
+
+    /// Sorted kmers and counter
+    Sparse,
+}
+
+/// Header of a count table file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    /// Kmer size
+    pub k: u8,
+
+    /// How kmers are store
+    pub mode: Mode,
+
+    /// Layout of body
+    pub body: Body,
+
+    /// Number of bytes of a counter
+    pub width: u8,
+
+    /// Number of entries in body
+    pub entries: u64,
+}
+
+impl Header {
+    /// Header of a dense table for kmer of size k, with counter of width bytes
+    pub fn dense(k: u8, width: u8) -> Self {
+        Header {
+            k,
+            mode: Mode::Hash,
+            body: Body::Dense,
+            width,
+            entries: dense_size(k),
+        }
+    }
+
+    /// Header of a sparse table for kmer of size k, with counter of width bytes and entries kmers
+    pub fn sparse(k: u8, mode: Mode, width: u8, entries: u64) -> Self {
+        Header {
+            k,
+            mode,
+            body: Body::Sparse,
+            width,
+            entries,
+        }
+    }
+
+    fn check(&self) -> Result<(), error::Error> {
+        match self.body {
+            Body::Dense => {
+                kmer::check_kmer_size(self.k, super::MAX_K)?;
+                if self.mode != Mode::Hash {
+                    return Err(format_error("dense body must be in hash mode"));
+                }
+                if self.entries != dense_size(self.k) {
+                    return Err(format_error("dense body size isn't coherent with k"));
+                }
+            }
+            Body::Sparse => kmer::check_kmer_size(self.k, 32)?,
+        }
+
+        if !matches!(self.width, 1 | 2 | 4) {
+            return Err(format_error("counter width must be 1, 2 or 4"));
+        }
+
+        Ok(())
+    }
+
+    fn to_bytes(self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0; HEADER_SIZE];
+
+        bytes[0..4].copy_from_slice(&SIGNATURE);
+        bytes[4] = VERSION;
+        bytes[5] = self.k;
+        bytes[6] = 0;
+        bytes[7] = match self.mode {
+            Mode::Canonical => 0,
+            Mode::Hash => 1,
+        };
+        bytes[8] = match self.body {
+            Body::Dense => 0,
+            Body::Sparse => 1,
+        };
+        bytes[9] = self.width;
+        bytes[12..20].copy_from_slice(&self.entries.to_le_bytes());
+
+        let mut checksum = Checksum::new();
+        checksum.update(&bytes[..20]);
+        bytes[20..].copy_from_slice(&checksum.0.to_le_bytes());
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8; HEADER_SIZE]) -> Result<Self, error::Error> {
+        if bytes[0..4] != SIGNATURE {
+            return Err(format_error("bad signature"));
+        }
+        if bytes[4] != VERSION {
+            return Err(error::Error::CountTableVersion {
+                found: bytes[4],
+                supported: VERSION,
+            });
+        }
+
+        let mut checksum = Checksum::new();
+        checksum.update(&bytes[..20]);
+        if checksum.0 != u64_le(&bytes[20..]) {
+            return Err(format_error("header checksum mismatch"));
+        }
+
+        if bytes[6] != 0 {
+            return Err(format_error("unsupported encoding"));
+        }
+
+        let header = Header {
+            k: bytes[5],
+            mode: match bytes[7] {
+                0 => Mode::Canonical,
+                1 => Mode::Hash,
+                _ => return Err(format_error("unsupported mode")),
+            },
+            body: match bytes[8] {
+                0 => Body::Dense,
+                1 => Body::Sparse,
+                _ => return Err(format_error("unsupported body")),
+            },
+            width: bytes[9],
+            entries: u64_le(&bytes[12..20]),
+        };
+        header.check()?;
+
+        Ok(header)
+    }
+}
+
+/// A streaming writer of count table, output should be buffered
+pub struct Writer<W> {
+    output: W,
+    header: Header,
+    written: u64,
+    last: Option<u64>,
+    checksum: Checksum,
+}
+
+impl<W> Writer<W>
+where
+    W: std::io::Write,
+{
+    /// Create a new Writer and write header, return an error if header isn't valid
+    pub fn new(mut output: W, header: Header) -> Result<Self, error::Error> {
+        header.check()?;
+        output.write_all(&header.to_bytes())?;
+
+        Ok(Writer {
+            output,
+            header,
+            written: 0,
+            last: None,
+            checksum: Checksum::new(),
+        })
+    }
+
+    /// Return header
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Write next counter of a dense body, count is saturated to counter width
+    pub fn push_count(&mut self, count: u32) -> Result<(), error::Error> {
+        if self.header.body != Body::Dense {
+            return Err(format_error("push_count can only be used on dense body"));
+        }
+
+        self.write_count(count)
+    }
+
+    /// Write a canonical kmer and count in a sparse body, kmers must be write in increasing order, count is saturated to counter width
+    ///
+    /// In [Mode::Hash] kmer must be canonical, see [canonical_any](kmer::canonical_any), otherwise it can't be read back.
+    pub fn push(&mut self, kmer: u64, count: u32) -> Result<(), error::Error> {
+        if self.header.body != Body::Sparse {
+            return Err(format_error("push can only be used on sparse body"));
+        }
+        if self.header.mode == Mode::Hash && kmer::canonical_any(kmer, self.header.k) != kmer {
+            return Err(format_error("kmer isn't canonical"));
+        }
+
+        let value = match self.header.mode {
+            Mode::Hash if self.header.k % 2 == 1 => kmer::remove_first_bit(kmer),
+            _ => kmer,
+        };
+        if self.last.is_some_and(|last| last >= value) {
+            return Err(format_error("kmers must be write in increasing order"));
+        }
+        self.last = Some(value);
+
+        let bytes = value.to_le_bytes();
+        self.checksum.update(&bytes);
+        self.output.write_all(&bytes)?;
+
+        self.write_count(count)
+    }
+
+    /// Write footer and return output, return an error if number of entries write isn't equal to header entries
+    pub fn finish(mut self) -> Result<W, error::Error> {
+        if self.written != self.header.entries {
+            return Err(format_error(
+                "number of entries isn't equal to header entries",
+            ));
+        }
+
+        self.output.write_all(&self.checksum.0.to_le_bytes())?;
+        self.output.flush()?;
+
+        Ok(self.output)
+    }
+
+    fn write_count(&mut self, count: u32) -> Result<(), error::Error> {
+        if self.written == self.header.entries {
+            return Err(format_error("more entries than header entries"));
+        }
+        self.written += 1;
+
+        let width = self.header.width as usize;
+        let max = u32::MAX >> (32 - 8 * width);
+        let bytes = count.min(max).to_le_bytes();
+
+        self.checksum.update(&bytes[..width]);
+        self.output.write_all(&bytes[..width])?;
+
+        Ok(())
+    }
+}
+
+/// A streaming reader of count table, it produce canonical kmers and count.
+///
+/// Counters equal to 0 of dense body are skipped. Footer checksum is check after last entry.
+/// Kmers of sparse body must be in increasing order and fit in kmer size, otherwise an error is produce.
+pub struct Reader<R> {
+    input: R,
+    header: Header,
+    read: u64,
+    last: Option<u64>,
+    checksum: Checksum,
+    done: bool,
+}
+
+impl<R> Reader<R>
+where
+    R: std::io::BufRead,
+{
+    /// Create a new Reader and read header, return an error if header isn't valid or version isn't supported
+    pub fn new(mut input: R) -> Result<Self, error::Error> {
+        let mut bytes = [0; HEADER_SIZE];
+        error::read_exact(&mut input, &mut bytes, TRUNCATED)?;
+
+        Ok(Reader {
+            input,
+            header: Header::from_bytes(&bytes)?,
+            read: 0,
+            last: None,
+            checksum: Checksum::new(),
+            done: false,
+        })
+    }
+
+    /// Same as [new](Reader::new) but return an error if kmer size of file isn't k
+    pub fn with_k(input: R, k: u8) -> Result<Self, error::Error> {
+        let reader = Self::new(input)?;
+
+        if reader.header.k != k {
+            return Err(error::Error::KmerSizeMismatch {
+                expected: k,
+                found: reader.header.k,
+            });
+        }
+
+        Ok(reader)
+    }
+
+    /// Return header
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Read next entry as store in file, hash for dense body, kmer or hash for sparse body, check footer after last entry
+    fn entry(&mut self) -> Result<Option<(u64, u32)>, error::Error> {
+        if self.read == self.header.entries {
+            if !self.done {
+                self.done = true;
+
+                let mut footer = [0; 8];
+                error::read_exact(&mut self.input, &mut footer, TRUNCATED)?;
+                if u64_le(&footer) != self.checksum.0 {
+                    return Err(format_error("body checksum mismatch"));
+                }
+            }
+
+            return Ok(None);
+        }
+
+        let value = match self.header.body {
+            Body::Dense => self.read,
+            Body::Sparse => {
+                let mut bytes = [0; 8];
+                error::read_exact(&mut self.input, &mut bytes, TRUNCATED)?;
+                self.checksum.update(&bytes);
+                let value = u64::from_le_bytes(bytes);
+
+                // value is a kmer on 2k bits or a hash on 2k - 1 bits for odd k
+                let bits = match self.header.mode {
+                    Mode::Hash if self.header.k % 2 == 1 => self.header.k * 2 - 1,
+                    _ => self.header.k * 2,
+                };
+                if value > u64::MAX >> (64 - bits) {
+                    return Err(format_error("kmer is larger than kmer size"));
+                }
+                if self.last.is_some_and(|last| last >= value) {
+                    return Err(format_error("kmers aren't in increasing order"));
+                }
+                self.last = Some(value);
+
+                value
+            }
+        };
+
+        let mut bytes = [0; 4];
+        let width = self.header.width as usize;
+        error::read_exact(&mut self.input, &mut bytes[..width], TRUNCATED)?;
+        self.checksum.update(&bytes[..width]);
+
+        self.read += 1;
+
+        Ok(Some((value, u32::from_le_bytes(bytes))))
+    }
+
+    fn to_kmer(&self, value: u64) -> u64 {
+        match self.header.mode {
+            Mode::Canonical => value,
+            Mode::Hash => kmer::hash2kmer(value, self.header.k),
+        }
+    }
+}
+
+impl<R> Iterator for Reader<R>
+where
+    R: std::io::BufRead,
+{
+    type Item = Result<(u64, u32), error::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.entry() {
+                Ok(Some((_, 0))) if self.header.body == Body::Dense => continue,
+                Ok(Some((value, count))) => return Some(Ok((self.to_kmer(value), count))),
+                Ok(None) => return None,
+                Err(e) => {
+                    self.done = true;
+                    self.read = self.header.entries;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Write a [DenseCounter] in a dense body
+pub fn write_dense<W, C>(output: W, counter: &DenseCounter<C>) -> Result<(), error::Error>
+where
+    W: std::io::Write,
+    C: Count,
+{
+    let mut writer = Writer::new(output, Header::dense(counter.k, C::WIDTH))?;
+    for count in counter.counts.iter() {
+        writer.push_count(count.to_u32())?;
+    }
+    writer.finish()?;
+
+    Ok(())
+}
+
+/// Write a [SparseCounter] in a sparse body, kmer are store in canonical mode
+pub fn write_sparse<W>(output: W, counter: &SparseCounter) -> Result<(), error::Error>
+where
+    W: std::io::Write,
+{
+    let kmers = counter.sorted();
+
+    let header = Header::sparse(counter.k, Mode::Canonical, 4, kmers.len() as u64);
+    let mut writer = Writer::new(output, header)?;
+    for (kmer, count) in kmers {
+        writer.push(kmer, count)?;
+    }
+    writer.finish()?;
+
+    Ok(())
+}
+
+/// Read a dense body in a [DenseCounter], count larger than C are saturated
+pub fn read_dense<R, C>(input: R) -> Result<DenseCounter<C>, error::Error>
+where
+    R: std::io::BufRead,
+    C: Count,
+{
+    let mut reader = Reader::new(input)?;
+    if reader.header.body != Body::Dense {
+        return Err(format_error("body isn't dense"));
+    }
+
+    // entries of header isn't trusted, counters are allocate when they are read
+    let mut counts = Vec::with_capacity((reader.header.entries as usize).min(1 << 16));
+    while let Some((_, count)) = reader.entry()? {
+        counts.push(C::from_u32(count));
+    }
+
+    Ok(DenseCounter {
+        k: reader.header.k,
+        counts: counts.into_boxed_slice(),
+    })
+}
+
+/// Read a sparse body in a [SparseCounter]
+pub fn read_sparse<R>(input: R) -> Result<SparseCounter, error::Error>
+where
+    R: std::io::BufRead,
+{
+    let mut reader = Reader::new(input)?;
+    if reader.header.body != Body::Sparse {
+        return Err(format_error("body isn't sparse"));
+    }
+
+    let mut counter = SparseCounter::try_new(reader.header.k)?;
+    while let Some((value, count)) = reader.entry()? {
+        counter.counts.insert(reader.to_kmer(value), count);
+    }
+
+    Ok(counter)
+}
+
+/// FNV-1a 64 bits
+#[derive(Debug, Clone, Copy)]
+struct Checksum(u64);
+
+impl Checksum {
+    fn new() -> Self {
+        Checksum(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+fn format_error(reason: &'static str) -> error::Error {
+    error::Error::CountTableFormat { reason }
+}
+
+fn u64_le(bytes: &[u8]) -> u64 {
+    let mut buffer = [0; 8];
+    buffer.copy_from_slice(bytes);
+
+    u64::from_le_bytes(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    /* project use */
+    use super::*;
+    use crate::test_utils::random_seq;
+    use crate::tokenizer;
+
+    #[test]
+    fn dense() {
+        let seq = random_seq(1000);
+
+        for k in [5, 6] {
+            let mut counter = DenseCounter::<u16>::new(k);
+            counter.add_seq(&seq);
+
+            let mut file = Vec::new();
+            write_dense(&mut file, &counter).unwrap();
+            assert_eq!(file.len(), HEADER_SIZE + dense_size(k) as usize * 2 + 8);
+
+            assert_eq!(read_dense::<_, u16>(&file[..]).unwrap(), counter);
+            assert_eq!(
+                Reader::with_k(&file[..], k)
+                    .unwrap()
+                    .collect::<Result<Vec<(u64, u32)>, error::Error>>()
+                    .unwrap(),
+                counter
+                    .iter()
+                    .map(|(kmer, count)| (kmer, count as u32))
+                    .collect::<Vec<(u64, u32)>>()
+            );
+
+            let small = read_dense::<_, u8>(&file[..]).unwrap();
+            assert_eq!(small.k(), k);
+        }
+    }
+
+    #[test]
+    fn sparse() {
+        let seq = random_seq(1000);
+
+        for k in [15, 16, 31] {
+            let mut counter = SparseCounter::new(k);
+            counter.extend(tokenizer::kmer::Canonical::<u64>::new(&seq, k));
+
+            let mut file = Vec::new();
+            write_sparse(&mut file, &counter).unwrap();
+
+            assert_eq!(read_sparse(&file[..]).unwrap(), counter);
+        }
+    }
+
+    #[test]
+    fn sparse_hash() {
+        let seq = random_seq(500);
+        let mut counter = SparseCounter::new(11);
+        counter.extend(tokenizer::kmer::Canonical::<u64>::new(&seq, 11));
+        let kmers = counter.sorted();
+
+        let header = Header::sparse(11, Mode::Hash, 1, kmers.len() as u64);
+        let mut writer = Writer::new(Vec::new(), header).unwrap();
+        for (kmer, count) in kmers.iter() {
+            writer.push(*kmer, *count).unwrap();
+        }
+        let file = writer.finish().unwrap();
+
+        let reader = Reader::new(&file[..]).unwrap();
+        assert_eq!(reader.header(), &header);
+        assert_eq!(
+            reader
+                .collect::<Result<Vec<(u64, u32)>, error::Error>>()
+                .unwrap(),
+            kmers
+        );
+    }
+
+    #[test]
+    fn hash_not_canonical() {
+        // AAAAC isn't canonical, GTTTT is
+        let forward = kmer::seq2bit(b"AAAAC");
+        let canonical = kmer::seq2bit(b"GTTTT");
+
+        let mut writer = Writer::new(Vec::new(), Header::sparse(5, Mode::Hash, 4, 1)).unwrap();
+        assert_eq!(
+            writer.push(forward, 1),
+            Err(error::Error::CountTableFormat {
+                reason: "kmer isn't canonical"
+            })
+        );
+        writer.push(canonical, 1).unwrap();
+        let file = writer.finish().unwrap();
+
+        assert_eq!(
+            Reader::new(&file[..])
+                .unwrap()
+                .collect::<Result<Vec<(u64, u32)>, error::Error>>()
+                .unwrap(),
+            vec![(canonical, 1)]
+        );
+
+        let mut writer = Writer::new(Vec::new(), Header::sparse(4, Mode::Hash, 4, 1)).unwrap();
+        assert!(writer.push(kmer::seq2bit(b"GTAA"), 1).is_err());
+        writer.push(kmer::seq2bit(b"TTAC"), 1).unwrap();
+    }
+
+    #[test]
+    fn writer_error() {
+        let mut writer = Writer::new(Vec::new(), Header::sparse(5, Mode::Canonical, 4, 2)).unwrap();
+        writer.push(10, 1).unwrap();
+        assert_eq!(
+            writer.push(9, 1),
+            Err(error::Error::CountTableFormat {
+                reason: "kmers must be write in increasing order"
+            })
+        );
+        assert!(writer.push_count(1).is_err());
+        assert!(writer.finish().is_err());
+
+        assert!(Writer::new(Vec::new(), Header::sparse(5, Mode::Canonical, 3, 2)).is_err());
+        assert!(Writer::new(Vec::new(), Header::dense(17, 1)).is_err());
+    }
+
+    #[test]
+    fn reader_error() {
+        let mut counter = SparseCounter::new(7);
+        counter.extend(tokenizer::kmer::Canonical::<u64>::new(b"ACTGACTGGA", 7));
+
+        let mut file = Vec::new();
+        write_sparse(&mut file, &counter).unwrap();
+
+        assert_eq!(
+            Reader::with_k(&file[..], 9).err(),
+            Some(error::Error::KmerSizeMismatch {
+                expected: 9,
+                found: 7
+            })
+        );
+
+        let mut version = file.clone();
+        version[4] = 2;
+        assert_eq!(
+            Reader::new(&version[..]).err(),
+            Some(error::Error::CountTableVersion {
+                found: 2,
+                supported: VERSION
+            })
+        );
+
+        let mut header = file.clone();
+        header[5] = 9;
+        assert_eq!(
+            Reader::new(&header[..]).err(),
+            Some(error::Error::CountTableFormat {
+                reason: "header checksum mismatch"
+            })
+        );
+
+        let mut body = file.clone();
+        body[HEADER_SIZE] ^= 1;
+        assert_eq!(
+            read_sparse(&body[..]),
+            Err(error::Error::CountTableFormat {
+                reason: "body checksum mismatch"
+            })
+        );
+
+        assert_eq!(
+            read_sparse(&file[..file.len() - 1]),
+            Err(error::Error::CountTableFormat {
+                reason: "unexpected end of file"
+            })
+        );
+
+        assert_eq!(
+            read_dense::<_, u8>(&file[..]),
+            Err(error::Error::CountTableFormat {
+                reason: "body isn't dense"
+            })
+        );
+
+        // a large dense header didn't allocate memory before data is read
+        let mut large = Vec::new();
+        Writer::new(&mut large, Header::dense(15, 4)).unwrap();
+        assert_eq!(
+            read_dense::<_, u32>(&large[..]),
+            Err(error::Error::CountTableFormat {
+                reason: "unexpected end of file"
+            })
+        );
+    }
+
+    #[test]
+    fn sparse_validation() {
+        let entries = |kmers: &[u64]| {
+            let mut file = Vec::new();
+            let mut writer = Writer::new(
+                &mut file,
+                Header::sparse(7, Mode::Canonical, 4, kmers.len() as u64),
+            )
+            .unwrap();
+            for kmer in kmers {
+                writer.push(*kmer, 1).unwrap();
+            }
+            writer.finish().unwrap();
+
+            file
+        };
+
+        assert!(read_sparse(&entries(&[1, 5, 1 << 13])[..]).is_ok());
+
+        // swap kmers, checksum is still valid
+        let mut unsorted = entries(&[1, 5]);
+        unsorted[HEADER_SIZE..HEADER_SIZE + 8].copy_from_slice(&5u64.to_le_bytes());
+        unsorted[HEADER_SIZE + 12..HEADER_SIZE + 20].copy_from_slice(&1u64.to_le_bytes());
+        assert_eq!(
+            read_sparse(&unsorted[..]),
+            Err(error::Error::CountTableFormat {
+                reason: "kmers aren't in increasing order"
+            })
+        );
+
+        let large = entries(&[1, 1 << 14]);
+        assert_eq!(
+            read_sparse(&large[..]),
+            Err(error::Error::CountTableFormat {
+                reason: "kmer is larger than kmer size"
+            })
+        );
+        assert!(Reader::new(&large[..])
+            .unwrap()
+            .any(|result| result.is_err()));
+    }
+}
@@ -0,0 +1,125 @@
+//! Error produce by cocktail
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+
+/// Error produce by cocktail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Length of sequence isn't equal to kmer size
+    SequenceLength { expected: usize, found: usize },
+
+    /// Byte at position isn't a valid nucleotide (A, C, T or G)
+    Nucleotide { byte: u8, position: usize },
+
+    /// Kmer size must be in 1..=max
+    KmerSize { k: u8, max: u8 },
+
+    /// Minimizer size must be in 1..=k
+    MinimizerSize { m: u8, k: u8 },
+
+    /// Input or output error, std::io::Error isn't cloneable so only kind and message are keep
+    Io {
+        kind: std::io::ErrorKind,
+        message: String,
+    },
+
+    /// File isn't a valid UCSC .2bit file
+    TwoBitFormat { reason: &'static str },
+
+    /// File isn't a valid FASTA or FASTQ file, line is the line number (start at 1) where error is detected
+    FastxFormat { reason: &'static str, line: usize },
+
+    /// File isn't a valid cocktail count table
+    CountTableFormat { reason: &'static str },
+
+    /// Version of count table file isn't supported
+    CountTableVersion { found: u8, supported: u8 },
+
+    /// Kmer size of data isn't equal to expected kmer size
+    KmerSizeMismatch { expected: u8, found: u8 },
+
+    /// Parameter of a sketch isn't valid
+    SketchParameter { reason: &'static str },
+
+    /// File isn't a valid sketch file
+    SketchFormat { reason: &'static str },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::SequenceLength { expected, found } => write!(
+                f,
+                "sequence length {} isn't equal to kmer size {}",
+                found, expected
+            ),
+            Error::Nucleotide { byte, position } => write!(
+                f,
+                "byte {:?} at position {} isn't a nucleotide",
+                char::from(*byte),
+                position
+            ),
+            Error::KmerSize { k, max } => {
+                write!(
+                    f,
+                    "kmer size {} isn't supported, it must be in 1..={}",
+                    k, max
+                )
+            }
+            Error::MinimizerSize { m, k } => write!(
+                f,
+                "minimizer size {} isn't supported, it must be in 1..={}",
+                m, k
+            ),
+            Error::Io { message, .. } => write!(f, "io error: {}", message),
+            Error::TwoBitFormat { reason } => write!(f, "invalid .2bit file: {}", reason),
+            Error::FastxFormat { reason, line } => {
+                write!(f, "invalid fasta/fastq file at line {}: {}", line, reason)
+            }
+            Error::CountTableFormat { reason } => {
+                write!(f, "invalid count table file: {}", reason)
+            }
+            Error::CountTableVersion { found, supported } => write!(
+                f,
+                "count table version {} isn't supported, only version {} is supported",
+                found, supported
+            ),
+            Error::KmerSizeMismatch { expected, found } => write!(
+                f,
+                "kmer size {} isn't equal to expected kmer size {}",
+                found, expected
+            ),
+            Error::SketchParameter { reason } => write!(f, "invalid sketch parameter: {}", reason),
+            Error::SketchFormat { reason } => write!(f, "invalid sketch file: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Read exactly buffer.len() bytes, if input is truncated return truncated error, other error are convert in [Error::Io]
+pub(crate) fn read_exact<R>(input: &mut R, buffer: &mut [u8], truncated: Error) -> Result<(), Error>
+where
+    R: std::io::Read,
+{
+    input.read_exact(buffer).map_err(|e| {
+        if e.kind() == std::io::ErrorKind::UnexpectedEof {
+            truncated
+        } else {
+            e.into()
+        }
+    })
+}
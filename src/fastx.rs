@@ -0,0 +1,316 @@
+//! A small streaming FASTA and FASTQ reader, records can be directly tokenize.
+//!
+//! FASTA sequence can be write on multiple lines, FASTQ record must be write on 4 lines. Format is detected for each record with the first byte of header (`>` or `@`).
+//!
+//! # Example
+//!
+//! ```
+//! use cocktail::fastx::Reader;
+//!
+//! let file = b">seq1 description\nACTGACTG\nGATC\n@seq2\nACTGA\n+\nIIIII\n";
+//!
+//! for record in Reader::new(&file[..]) {
+//!     let record = record.unwrap();
+//!
+//!     for kmer in record.canonical_kmers(5) {
+//!         // ... do what you want ...
+//!     }
+//! }
+//! ```
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+use crate::error;
+use crate::tokenizer;
+use crate::tokenizer::minimizer::method;
+
+/// A FASTA or FASTQ record
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Record {
+    header: Vec<u8>,
+    seq: Vec<u8>,
+    qual: Option<Vec<u8>>,
+}
+
+impl Record {
+    /// Return header line without `>` or `@`
+    pub fn header(&self) -> &[u8] {
+        &self.header
+    }
+
+    /// Return the first word of header
+    pub fn id(&self) -> &[u8] {
+        self.header
+            .split(|c| c.is_ascii_whitespace())
+            .next()
+            .unwrap_or(&self.header)
+    }
+
+    /// Return sequence, lines of FASTA record are concatenated
+    pub fn seq(&self) -> &[u8] {
+        &self.seq
+    }
+
+    /// Return quality, None for FASTA record
+    pub fn qual(&self) -> Option<&[u8]> {
+        self.qual.as_deref()
+    }
+
+    /// Return a tokenizer that produce kmers of sequence in forward orientation, see [Forward](tokenizer::kmer::Forward)
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32
+    pub fn kmers(&self, k: u8) -> tokenizer::kmer::Forward<'_, u64> {
+        tokenizer::kmer::Forward::new(&self.seq, k)
+    }
+
+    /// Return a tokenizer that produce kmers of sequence in canonical orientation, see [Canonical](tokenizer::kmer::Canonical)
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32
+    pub fn canonical_kmers(&self, k: u8) -> tokenizer::kmer::Canonical<'_, u64> {
+        tokenizer::kmer::Canonical::new(&self.seq, k)
+    }
+
+    /// Return a tokenizer that produce canonical kmers of sequence and associate minimizer, see [Canonical](tokenizer::minimizer::Canonical)
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32 or m isn't in 1..=k
+    pub fn minimizers(
+        &self,
+        k: u8,
+        m: u8,
+    ) -> tokenizer::minimizer::Canonical<'_, method::Random, u64> {
+        tokenizer::minimizer::Canonical::<method::Random, u64>::new(&self.seq, k, m)
+    }
+}
+
+/// An iterator that read FASTA or FASTQ records from input
+pub struct Reader<R> {
+    input: R,
+    line: usize,
+    buffer: Vec<u8>,
+}
+
+impl<R> Reader<R>
+where
+    R: std::io::BufRead,
+{
+    /// Create a new Reader on input
+    pub fn new(input: R) -> Self {
+        Reader {
+            input,
+            line: 0,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn read_record(&mut self) -> Result<Option<Record>, error::Error> {
+        // skip empty lines before record
+        loop {
+            match self.peek()? {
+                None => return Ok(None),
+                Some(b'\n') | Some(b'\r') => {
+                    self.read_line()?;
+                }
+                Some(_) => break,
+            }
+        }
+
+        self.read_line()?;
+        let header = self.buffer[1..].to_vec();
+
+        match self.buffer[0] {
+            b'>' => {
+                let mut seq = Vec::new();
+                while self.peek()?.is_some_and(|c| c != b'>' && c != b'@') {
+                    self.read_line()?;
+                    seq.extend_from_slice(&self.buffer);
+                }
+
+                Ok(Some(Record {
+                    header,
+                    seq,
+                    qual: None,
+                }))
+            }
+            b'@' => {
+                if !self.read_line()? {
+                    return Err(self.error("fastq record is truncated"));
+                }
+                let seq = self.buffer.clone();
+
+                if !self.read_line()? || self.buffer.first() != Some(&b'+') {
+                    return Err(self.error("fastq separator line must start with '+'"));
+                }
+
+                if !self.read_line()? {
+                    return Err(self.error("fastq record is truncated"));
+                }
+                if self.buffer.len() != seq.len() {
+                    return Err(self.error("quality length isn't equal to sequence length"));
+                }
+
+                Ok(Some(Record {
+                    header,
+                    seq,
+                    qual: Some(self.buffer.clone()),
+                }))
+            }
+            _ => Err(self.error("record header must start with '>' or '@'")),
+        }
+    }
+
+    /// Read a line in buffer without end of line, return false if input is empty
+    fn read_line(&mut self) -> Result<bool, error::Error> {
+        self.buffer.clear();
+        if self.input.read_until(b'\n', &mut self.buffer)? == 0 {
+            return Ok(false);
+        }
+        self.line += 1;
+
+        if self.buffer.last() == Some(&b'\n') {
+            self.buffer.pop();
+        }
+        if self.buffer.last() == Some(&b'\r') {
+            self.buffer.pop();
+        }
+
+        Ok(true)
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, error::Error> {
+        Ok(self.input.fill_buf()?.first().copied())
+    }
+
+    fn error(&self, reason: &'static str) -> error::Error {
+        error::Error::FastxFormat {
+            reason,
+            line: self.line,
+        }
+    }
+}
+
+impl<R> Iterator for Reader<R>
+where
+    R: std::io::BufRead,
+{
+    type Item = Result<Record, error::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fasta() {
+        let file = b">seq1 description\nACTGACTG\nGATC\n\n>seq2\r\nACTGA\r\nAA\r\n>empty\n";
+
+        let records = Reader::new(&file[..])
+            .collect::<Result<Vec<Record>, error::Error>>()
+            .unwrap();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].header(), b"seq1 description");
+        assert_eq!(records[0].id(), b"seq1");
+        assert_eq!(records[0].seq(), b"ACTGACTGGATC");
+        assert_eq!(records[0].qual(), None);
+        assert_eq!(records[1].id(), b"seq2");
+        assert_eq!(records[1].seq(), b"ACTGAAA");
+        assert_eq!(records[2].seq(), b"");
+    }
+
+    #[test]
+    fn fastq() {
+        let file = b"\n@read1\nACTGA\n+read1\nIIII#\n@read2\nAC\n+\n@@\n>seq3\nACTG";
+
+        let records = Reader::new(&file[..])
+            .collect::<Result<Vec<Record>, error::Error>>()
+            .unwrap();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].id(), b"read1");
+        assert_eq!(records[0].seq(), b"ACTGA");
+        assert_eq!(records[0].qual(), Some(&b"IIII#"[..]));
+        assert_eq!(records[1].qual(), Some(&b"@@"[..]));
+        assert_eq!(records[2].seq(), b"ACTG");
+    }
+
+    #[test]
+    fn mixed() {
+        let file = b">seq1\nACTG\nGA\n@read1\nACT\n+\nII#\n>seq2\nTTGA\n@read2\nA\n+\nI\n";
+
+        let records = Reader::new(&file[..])
+            .collect::<Result<Vec<Record>, error::Error>>()
+            .unwrap();
+
+        assert_eq!(
+            records.iter().map(|r| r.id()).collect::<Vec<&[u8]>>(),
+            vec![&b"seq1"[..], b"read1", b"seq2", b"read2"]
+        );
+        assert_eq!(records[0].seq(), b"ACTGGA");
+        assert_eq!(records[0].qual(), None);
+        assert_eq!(records[1].qual(), Some(&b"II#"[..]));
+        assert_eq!(records[2].seq(), b"TTGA");
+        assert_eq!(records[3].seq(), b"A");
+    }
+
+    #[test]
+    fn error() {
+        assert_eq!(
+            Reader::new(&b"ACTG\n"[..]).next(),
+            Some(Err(error::Error::FastxFormat {
+                reason: "record header must start with '>' or '@'",
+                line: 1
+            }))
+        );
+
+        assert_eq!(
+            Reader::new(&b"@read1\nACTGA\n+\nIII\n"[..]).next(),
+            Some(Err(error::Error::FastxFormat {
+                reason: "quality length isn't equal to sequence length",
+                line: 4
+            }))
+        );
+
+        assert_eq!(
+            Reader::new(&b"@read1\nACTGA\n"[..]).next(),
+            Some(Err(error::Error::FastxFormat {
+                reason: "fastq separator line must start with '+'",
+                line: 2
+            }))
+        );
+    }
+
+    #[test]
+    fn tokenize() {
+        let record = Reader::new(&b">seq1\nACTG\nACTG\n"[..])
+            .next()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            record.kmers(5).collect::<Vec<u64>>(),
+            vec![108, 433, 710, 795]
+        );
+        assert_eq!(
+            record.canonical_kmers(5).collect::<Vec<u64>>(),
+            tokenizer::kmer::Canonical::<u64>::new(b"ACTGACTG", 5).collect::<Vec<u64>>()
+        );
+        assert_eq!(
+            record.minimizers(5, 3).collect::<Vec<(u64, u64)>>(),
+            tokenizer::minimizer::Canonical::<method::Random, u64>::new(b"ACTGACTG", 5, 3)
+                .collect::<Vec<(u64, u64)>>()
+        );
+    }
+}
@@ -1,22 +1,31 @@
 //! A set of function to convert small sequence (less than 32 nucleotide) in 2 bit representation.
+//! Trait [Kmer] provide same function for all unsigned integer, a `u128` can store sequence up to 64 nucleotide.
+//! Function with `_u128` suffix are deprecated wrapper around [Kmer] implementation of `u128`.
 //! - A or a -> 00
 //! - C or c -> 01
 //! - T or t -> 10
 //! - G or g -> 11
 //!
 //! We use the second and thrid bit of each value provide, if you provide no ACTG value this function silently convert to A, C, T or G, for exemple N or n is convert in G.
+//! Function with `_checked` suffix return an [Error::Nucleotide](crate::error::Error::Nucleotide) on no ACTG value.
 //!
 //! With this coding and if kmer size is odd, if the popcount of forward is odd the popcount of reverse is even. In this library if a kmer have even popcount is the canonical kmer.
 //!
 //! If we work only with canonical kmer, we can remove one bit at any extremity. To reconstruct lost bit, if result have even popcount we add a 0, if it's ood we add 1.
+//! [add_first_bit] and [hash2kmer] do this reconstruction.
+//!
+//! If kmer size is even, forward and reverse have the same parity, [canonical] and [hash] didn't work. Use [canonical_min], the smallest value between forward and reverse, and [hash_min] for even kmer size.
+//! With this canonical form no bit can be removed, the hash space is equal to the kmer space, see [get_kmer_space_size]. Canonical tokenizers select the right method according to kmer size parity, like [canonical_any].
 //!
 //! This 2bit coding is inspired by https://cs.stackexchange.com/questions/82644/compact-mapping-from-an-involuted-set
 
 /* standard use */
+use std::ops;
 
 /* crates use */
 
 /* project use */
+use crate::error;
 
 /// Convert a sequence in 2 bit representation if suseq is larger than 32 only the last 32 nuc is store
 #[inline(always)]
@@ -37,18 +46,133 @@ pub fn nuc2bit(nuc: u8) -> u64 {
     (nuc as u64 >> 1) & 0b11
 }
 
+/// Return true if nuc is A, C, T or G in upper or lower case
+#[inline(always)]
+pub fn is_nuc(nuc: u8) -> bool {
+    matches!(nuc, b'A' | b'C' | b'T' | b'G' | b'a' | b'c' | b't' | b'g')
+}
+
+/// Check all byte of seq is a nucleotide, else return position of first invalid byte
+#[inline(always)]
+pub fn validate(seq: &[u8]) -> Result<(), error::Error> {
+    match seq.iter().position(|n| !is_nuc(*n)) {
+        Some(position) => Err(error::Error::Nucleotide {
+            byte: seq[position],
+            position,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Check kmer size k is in 1..=max
+#[inline(always)]
+pub fn check_kmer_size(k: u8, max: u8) -> Result<(), error::Error> {
+    if k == 0 || k > max {
+        Err(error::Error::KmerSize { k, max })
+    } else {
+        Ok(())
+    }
+}
+
+/// Check minimizer size m is in 1..=k
+#[inline(always)]
+pub fn check_minimizer_size(k: u8, m: u8) -> Result<(), error::Error> {
+    if m == 0 || m > k {
+        Err(error::Error::MinimizerSize { m, k })
+    } else {
+        Ok(())
+    }
+}
+
+/// Convert a nucleotide in 2bit representation, return an error if nuc isn't a nucleotide, position in error is always 0
+#[inline(always)]
+pub fn nuc2bit_checked(nuc: u8) -> Result<u64, error::Error> {
+    if is_nuc(nuc) {
+        Ok(nuc2bit(nuc))
+    } else {
+        Err(error::Error::Nucleotide {
+            byte: nuc,
+            position: 0,
+        })
+    }
+}
+
+/// Convert a sequence in 2 bit representation, return an error on the first byte that isn't a nucleotide
+#[inline(always)]
+pub fn seq2bit_checked(subseq: &[u8]) -> Result<u64, error::Error> {
+    validate(subseq)?;
+
+    Ok(seq2bit(subseq))
+}
+
 /// Convert a 2 bit repersentation in String
 #[inline(always)]
-pub fn kmer2seq(mut kmer: u64, k: u8) -> Vec<u8> {
-    let mut buffer: [u8; 31] = [0; 31];
+pub fn kmer2seq(kmer: u64, k: u8) -> Vec<u8> {
+    let mut buffer: [u8; 32] = [0; 32];
 
-    for i in (0..k).rev() {
-        buffer[i as usize] = bit2nuc(kmer & 0b11);
+    kmer2seq_into(kmer, k, &mut buffer);
+
+    buffer[..k as usize].to_vec()
+}
+
+/// Convert a 2 bit representation in sequence, write in the k first bytes of buffer, no allocation is perform
+///
+/// # Panics
+///
+/// If buffer is shorter than k
+#[inline(always)]
+pub fn kmer2seq_into(mut kmer: u64, k: u8, buffer: &mut [u8]) {
+    for nuc in buffer[..k as usize].iter_mut().rev() {
+        *nuc = bit2nuc(kmer & 0b11);
 
         kmer >>= 2;
     }
+}
 
-    buffer[..k as usize].to_vec()
+/// Convert a 2 bit representation in sequence and write it in out
+#[inline(always)]
+pub fn kmer2seq_write<W>(kmer: u64, k: u8, out: &mut W) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    let mut buffer: [u8; 32] = [0; 32];
+
+    kmer2seq_into(kmer, k, &mut buffer);
+
+    out.write_all(&buffer[..k as usize])
+}
+
+/// Convert a batch of 2 bit representation in sequence, kmer i is write in `buffer[i * k..(i + 1) * k]`
+///
+/// # Panics
+///
+/// If buffer is shorter than `kmers.len() * k`
+#[inline(always)]
+pub fn kmers2seqs_into(kmers: &[u64], k: u8, buffer: &mut [u8]) {
+    assert!(
+        buffer.len() >= kmers.len() * k as usize,
+        "buffer is too short to store all kmers"
+    );
+
+    for (kmer, chunk) in kmers.iter().zip(buffer.chunks_exact_mut(k as usize)) {
+        kmer2seq_into(*kmer, k, chunk);
+    }
+}
+
+/// Convert a batch of 2 bit representation in sequence and write them in out, each kmer is followed by a new line
+pub fn kmers2seqs_write<W>(kmers: &[u64], k: u8, out: &mut W) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    let mut buffer: [u8; 33] = [b'\n'; 33];
+
+    for kmer in kmers {
+        kmer2seq_into(*kmer, k, &mut buffer);
+
+        out.write_all(&buffer[..=k as usize])?;
+    }
+
+    Ok(())
 }
 
 /// Convert the 2bit representation of a nucleotide in nucleotide
@@ -73,6 +197,22 @@ pub fn canonical(kmer: u64, k: u8) -> u64 {
     }
 }
 
+/// Take a kmer and return the canonical form, the smallest value between kmer and his reverse complement, work for odd and even k
+#[inline(always)]
+pub fn canonical_min(kmer: u64, k: u8) -> u64 {
+    kmer.min(revcomp(kmer, k))
+}
+
+/// Take a kmer and return the canonical form used by canonical tokenizers, [canonical] if k is odd and [canonical_min] if k is even
+#[inline(always)]
+pub fn canonical_any(kmer: u64, k: u8) -> u64 {
+    if k % 2 == 1 {
+        canonical(kmer, k)
+    } else {
+        canonical_min(kmer, k)
+    }
+}
+
 /// Return true if the kmer parity is even
 #[inline(always)]
 pub fn parity_even(kmer: u64) -> bool {
@@ -93,7 +233,7 @@ fn speed_comp(kmer: u64) -> u64 {
 /// Return the complement of kmer
 #[inline(always)]
 pub fn comp(kmer: u64, k: u8) -> u64 {
-    speed_comp(kmer) & ((1 << (2 * k)) - 1)
+    speed_comp(kmer) & (u64::MAX >> (64 - 2 * k))
 }
 
 /// Return true if the right bit of kmer is 1
@@ -114,6 +254,40 @@ pub fn hash(subseq: &[u8], k: u8) -> u64 {
     remove_first_bit(canonical(seq2bit(subseq), k))
 }
 
+/// Return the kmer with the rightest bit restored, 0 if popcount of hash is even, 1 if it's odd
+#[inline(always)]
+pub fn add_first_bit(hash: u64) -> u64 {
+    (hash << 1) | (hash.count_ones() as u64 & 1)
+}
+
+/// Take a hash, build by [hash] if k is odd or [hash_min] if k is even, and return the canonical kmer
+#[inline(always)]
+pub fn hash2kmer(hash: u64, k: u8) -> u64 {
+    if k % 2 == 1 {
+        add_first_bit(hash)
+    } else {
+        hash
+    }
+}
+
+/// Take a hash, build by [hash] if k is odd or [hash_min] if k is even, and return the canonical kmer sequence
+#[inline(always)]
+pub fn hash2seq(hash: u64, k: u8) -> Vec<u8> {
+    kmer2seq(hash2kmer(hash, k), k)
+}
+
+/// Convert a batch of hash in canonical kmer, see [hash2kmer]
+#[inline(always)]
+pub fn hashes2kmers(hashes: &[u64], k: u8) -> Vec<u64> {
+    hashes.iter().map(|hash| hash2kmer(*hash, k)).collect()
+}
+
+/// Take a subseq and return the canonical kmer with [canonical_min], hash equivalent for even k, no bit is removed
+#[inline(always)]
+pub fn hash_min(subseq: &[u8], k: u8) -> u64 {
+    canonical_min(seq2bit(subseq), k)
+}
+
 /// Return the reverse of kmer
 #[inline(always)]
 pub fn rev(mut kmer: u64, k: u8) -> u64 {
@@ -127,18 +301,340 @@ pub fn rev(mut kmer: u64, k: u8) -> u64 {
     kmer >> (64 - k * 2)
 }
 
-/// Return the cardinality of canonical kmer set for a given kmer size
+/// Return the cardinality of canonical kmer set for a given kmer size, k must be lower than 32, see [get_kmer_space_size_u128] for larger k
 #[inline(always)]
 pub fn get_kmer_space_size(k: u8) -> u64 {
     1 << (k * 2)
 }
 
+/// Return the cardinality of canonical kmer set for a given kmer size, k must be lower than 64
+#[inline(always)]
+pub fn get_kmer_space_size_u128(k: u8) -> u128 {
+    u128::kmer_mask(k) + 1
+}
+
 /// Return the cardinality of canonical hash set for a given kmer size
 #[inline(always)]
 pub fn get_hash_space_size(k: u8) -> u64 {
     1 << (k * 2 - 1)
 }
 
+/// Mix bits of a kmer, murmur3 finalizer, each bit of result depends of all bits of kmer
+#[inline(always)]
+pub fn mix(kmer: u64) -> u64 {
+    let mut hash = kmer;
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+/// Inverse of [mix]
+#[inline(always)]
+pub fn unmix(hash: u64) -> u64 {
+    let mut kmer = hash;
+    kmer ^= kmer >> 33;
+    kmer = kmer.wrapping_mul(0x9cb4_b2f8_1293_37db);
+    kmer ^= kmer >> 33;
+    kmer = kmer.wrapping_mul(0x4f74_430c_22a5_4005);
+    kmer ^ (kmer >> 33)
+}
+
+/// Convert a sequence in 2 bit representation if subseq is larger than 64 only the last 64 nuc is store
+#[deprecated(note = "use Kmer::seq2bit on u128")]
+#[inline(always)]
+pub fn seq2bit_u128(subseq: &[u8]) -> u128 {
+    u128::seq2bit(subseq)
+}
+
+/// Convert a nucleotide in 2bit representation
+#[deprecated(note = "use Kmer::nuc2bit on u128")]
+#[inline(always)]
+pub fn nuc2bit_u128(nuc: u8) -> u128 {
+    u128::nuc2bit(nuc)
+}
+
+/// Convert a 2 bit repersentation in String
+#[deprecated(note = "use Kmer::kmer2seq on u128")]
+#[inline(always)]
+pub fn kmer2seq_u128(kmer: u128, k: u8) -> Vec<u8> {
+    kmer.kmer2seq(k)
+}
+
+/// Take a kmer and return the canonical form
+#[deprecated(note = "use Kmer::canonical on u128")]
+#[inline(always)]
+pub fn canonical_u128(kmer: u128, k: u8) -> u128 {
+    kmer.canonical(k)
+}
+
+/// Return true if the kmer parity is even
+#[deprecated(note = "use Kmer::parity_even on u128")]
+#[inline(always)]
+pub fn parity_even_u128(kmer: u128) -> bool {
+    kmer.parity_even()
+}
+
+/// Return the reverse complement of kmer
+#[deprecated(note = "use Kmer::revcomp on u128")]
+#[inline(always)]
+pub fn revcomp_u128(kmer: u128, k: u8) -> u128 {
+    kmer.revcomp(k)
+}
+
+/// Return the complement of kmer
+#[deprecated(note = "use Kmer::comp on u128")]
+#[inline(always)]
+pub fn comp_u128(kmer: u128, k: u8) -> u128 {
+    kmer.comp(k)
+}
+
+/// Return the kmer without the rightest bit of kmer
+#[deprecated(note = "use Kmer::remove_first_bit on u128")]
+#[inline(always)]
+pub fn remove_first_bit_u128(kmer: u128) -> u128 {
+    kmer.remove_first_bit()
+}
+
+/// Take a subseq and return the canonical kmer with out the rightest bit
+#[deprecated(note = "use Kmer::hash on u128")]
+#[inline(always)]
+pub fn hash_u128(subseq: &[u8], k: u8) -> u128 {
+    <u128 as Kmer>::hash(subseq, k)
+}
+
+/// Return the reverse of kmer
+#[deprecated(note = "use Kmer::rev on u128")]
+#[inline(always)]
+pub fn rev_u128(kmer: u128, k: u8) -> u128 {
+    Kmer::rev(kmer, k)
+}
+
+/// Return the cardinality of canonical hash set for a given kmer size
+#[deprecated(note = "compute 1 << (2 * k - 1) on u128")]
+#[inline(always)]
+pub fn get_hash_space_size_u128(k: u8) -> u128 {
+    1 << (k * 2 - 1)
+}
+
+/// A unsigned integer that can store a kmer in 2 bit representation.
+///
+/// Implemented for `u8`, `u16`, `u32`, `u64` and `u128`, a type can store kmer up to `MAX_K` nucleotide (`BITS / 2`).
+/// Method behave like the free function of this module with same name.
+///
+/// # Example
+///
+/// ```
+/// use cocktail::kmer::Kmer;
+///
+/// let kmer = u32::seq2bit(b"TAGGC");
+///
+/// assert_eq!(kmer.canonical(5), kmer);
+/// assert_eq!(kmer.revcomp(5), u32::seq2bit(b"GCCTA"));
+/// assert_eq!(u32::hash(b"GCCTA", 5), 0b100011110);
+/// ```
+pub trait Kmer:
+    Copy
+    + Default
+    + core::fmt::Debug
+    + Eq
+    + Ord
+    + From<u8>
+    + ops::Shl<u8, Output = Self>
+    + ops::Shr<u8, Output = Self>
+    + ops::BitAnd<Output = Self>
+    + ops::BitOr<Output = Self>
+    + ops::BitXor<Output = Self>
+{
+    /// Number of bit of type
+    const BITS: u8;
+
+    /// Largest kmer size this type can store
+    const MAX_K: u8 = Self::BITS / 2;
+
+    /// Value with all bit set
+    const MAX: Self;
+
+    /// Mask use to complement all nucleotide
+    const COMP_MASK: Self;
+
+    /// Return the reverse of kmer
+    fn rev(self, k: u8) -> Self;
+
+    /// Return true if the kmer parity is even
+    fn parity_even(self) -> bool;
+
+    /// Return the lowest 64 bit of kmer
+    fn as_u64(self) -> u64;
+
+    /// Convert a nucleotide in 2bit representation
+    #[inline(always)]
+    fn nuc2bit(nuc: u8) -> Self {
+        (Self::from(nuc) >> 1) & Self::from(0b11)
+    }
+
+    /// Convert a sequence in 2 bit representation if subseq is larger than `MAX_K` only the last `MAX_K` nuc is store
+    #[inline(always)]
+    fn seq2bit(subseq: &[u8]) -> Self {
+        let mut kmer = Self::default();
+
+        for n in subseq {
+            kmer = (kmer << 2) | Self::nuc2bit(*n);
+        }
+
+        kmer
+    }
+
+    /// Convert a nucleotide in 2bit representation, return an error if nuc isn't a nucleotide, position in error is always 0
+    #[inline(always)]
+    fn nuc2bit_checked(nuc: u8) -> Result<Self, error::Error> {
+        nuc2bit_checked(nuc).map(|_| Self::nuc2bit(nuc))
+    }
+
+    /// Convert a sequence in 2 bit representation, return an error on the first byte that isn't a nucleotide
+    #[inline(always)]
+    fn seq2bit_checked(subseq: &[u8]) -> Result<Self, error::Error> {
+        validate(subseq)?;
+
+        Ok(Self::seq2bit(subseq))
+    }
+
+    /// Convert a 2 bit repersentation in String
+    #[inline(always)]
+    fn kmer2seq(self, k: u8) -> Vec<u8> {
+        let mut buffer = vec![0; k as usize];
+
+        self.kmer2seq_into(k, &mut buffer);
+
+        buffer
+    }
+
+    /// Convert a 2 bit representation in sequence, write in the k first bytes of buffer, see [kmer2seq_into](crate::kmer::kmer2seq_into)
+    #[inline(always)]
+    fn kmer2seq_into(mut self, k: u8, buffer: &mut [u8]) {
+        for nuc in buffer[..k as usize].iter_mut().rev() {
+            *nuc = bit2nuc((self & Self::from(0b11)).as_u64());
+
+            self = self >> 2;
+        }
+    }
+
+    /// Return a mask that keep only the 2 * k lowest bit
+    #[inline(always)]
+    fn kmer_mask(k: u8) -> Self {
+        Self::MAX >> (Self::BITS - k * 2)
+    }
+
+    /// Take a kmer and return the canonical form
+    #[inline(always)]
+    fn canonical(self, k: u8) -> Self {
+        if self.parity_even() {
+            self
+        } else {
+            self.revcomp(k)
+        }
+    }
+
+    /// Take a kmer and return the smallest value between kmer and his reverse complement, work for odd and even k
+    #[inline(always)]
+    fn canonical_min(self, k: u8) -> Self {
+        self.min(self.revcomp(k))
+    }
+
+    /// Take a kmer and return [canonical](Kmer::canonical) if k is odd and [canonical_min](Kmer::canonical_min) if k is even
+    #[inline(always)]
+    fn canonical_any(self, k: u8) -> Self {
+        if k % 2 == 1 {
+            self.canonical(k)
+        } else {
+            self.canonical_min(k)
+        }
+    }
+
+    /// Return the reverse complement of kmer
+    #[inline(always)]
+    fn revcomp(self, k: u8) -> Self {
+        (self ^ Self::COMP_MASK).rev(k)
+    }
+
+    /// Return the complement of kmer
+    #[inline(always)]
+    fn comp(self, k: u8) -> Self {
+        (self ^ Self::COMP_MASK) & Self::kmer_mask(k)
+    }
+
+    /// Return the kmer without the rightest bit of kmer
+    #[inline(always)]
+    fn remove_first_bit(self) -> Self {
+        self >> 1
+    }
+
+    /// Return the kmer with the rightest bit restored, 0 if popcount of hash is even, 1 if it's odd
+    #[inline(always)]
+    fn add_first_bit(self) -> Self {
+        if self.parity_even() {
+            self << 1
+        } else {
+            (self << 1) | Self::from(1)
+        }
+    }
+
+    /// Take a hash, build by [hash](Kmer::hash) if k is odd or [hash_min](Kmer::hash_min) if k is even, and return the canonical kmer
+    #[inline(always)]
+    fn hash2kmer(self, k: u8) -> Self {
+        if k % 2 == 1 {
+            self.add_first_bit()
+        } else {
+            self
+        }
+    }
+
+    /// Take a subseq and return the canonical kmer with out the rightest bit
+    #[inline(always)]
+    fn hash(subseq: &[u8], k: u8) -> Self {
+        Self::seq2bit(subseq).canonical(k).remove_first_bit()
+    }
+
+    /// Take a subseq and return the canonical kmer with [canonical_min](Kmer::canonical_min), hash equivalent for even k
+    #[inline(always)]
+    fn hash_min(subseq: &[u8], k: u8) -> Self {
+        Self::seq2bit(subseq).canonical_min(k)
+    }
+}
+
+macro_rules! impl_kmer {
+    ($($t:ty),*) => {
+        $(
+            impl Kmer for $t {
+                const BITS: u8 = <$t>::BITS as u8;
+                const MAX: Self = <$t>::MAX;
+                const COMP_MASK: Self = <$t>::MAX / 3 * 2;
+
+                #[inline(always)]
+                fn rev(self, k: u8) -> Self {
+                    // swap the two bit of each nucleotide, then reverse all bit
+                    let kmer = ((self >> 1) & (<$t>::MAX / 3)) | ((self & (<$t>::MAX / 3)) << 1);
+
+                    kmer.reverse_bits() >> (<Self as Kmer>::BITS - k * 2)
+                }
+
+                #[inline(always)]
+                fn parity_even(self) -> bool {
+                    self.count_ones() % 2 == 0
+                }
+
+                #[inline(always)]
+                fn as_u64(self) -> u64 {
+                    self as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_kmer!(u8, u16, u32, u64, u128);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,6 +648,62 @@ mod tests {
         assert_eq!(seq2bit(b"GCCTA"), 0b1101011000);
     }
 
+    #[test]
+    fn checked() {
+        assert!(is_nuc(b'a'));
+        assert!(is_nuc(b'G'));
+        assert!(!is_nuc(b'N'));
+        assert!(!is_nuc(b'-'));
+
+        assert_eq!(nuc2bit_checked(b'T'), Ok(0b10));
+        assert_eq!(
+            nuc2bit_checked(b'N'),
+            Err(error::Error::Nucleotide {
+                byte: b'N',
+                position: 0
+            })
+        );
+
+        assert_eq!(seq2bit_checked(b"TAGGC"), Ok(0b1000111101));
+        assert_eq!(
+            seq2bit_checked(b"TAGnC"),
+            Err(error::Error::Nucleotide {
+                byte: b'n',
+                position: 3
+            })
+        );
+        assert_eq!(<u16 as Kmer>::seq2bit_checked(b"TAGGC"), Ok(0b1000111101));
+        assert!(<u128 as Kmer>::seq2bit_checked(b"TAGGR").is_err());
+        assert_eq!(<u8 as Kmer>::nuc2bit_checked(b'c'), Ok(0b01));
+
+        assert_eq!(validate(b"ACTGactg"), Ok(()));
+        assert!(validate(b"ACTGNactg").is_err());
+    }
+
+    #[test]
+    fn check_size() {
+        assert_eq!(check_kmer_size(31, 32), Ok(()));
+        assert_eq!(check_kmer_size(32, 32), Ok(()));
+        assert_eq!(
+            check_kmer_size(0, 32),
+            Err(error::Error::KmerSize { k: 0, max: 32 })
+        );
+        assert_eq!(
+            check_kmer_size(33, 32),
+            Err(error::Error::KmerSize { k: 33, max: 32 })
+        );
+
+        assert_eq!(check_minimizer_size(31, 31), Ok(()));
+        assert_eq!(
+            check_minimizer_size(31, 0),
+            Err(error::Error::MinimizerSize { m: 0, k: 31 })
+        );
+        assert_eq!(
+            check_minimizer_size(11, 12),
+            Err(error::Error::MinimizerSize { m: 12, k: 11 })
+        );
+    }
+
     #[test]
     fn bit2seq_() {
         // 1000111101 -> TAGGC
@@ -164,6 +716,44 @@ mod tests {
             kmer2seq(0b1101011000, 31),
             b"AAAAAAAAAAAAAAAAAAAAAAAAAAGCCTA".to_vec()
         );
+
+        assert_eq!(
+            kmer2seq(seq2bit(b"GACTGACTGGATCAATGGACACGTACGATCGA"), 32),
+            b"GACTGACTGGATCAATGGACACGTACGATCGA".to_vec()
+        );
+    }
+
+    #[test]
+    fn kmer2seq_into_() {
+        let mut buffer = [b'N'; 8];
+
+        kmer2seq_into(0b1000111101, 5, &mut buffer);
+        assert_eq!(&buffer, b"TAGGCNNN");
+
+        let mut out = Vec::new();
+        kmer2seq_write(0b1000111101, 5, &mut out).unwrap();
+        kmer2seq_write(u64::MAX, 32, &mut out).unwrap();
+        assert_eq!(out, [&b"TAGGC"[..], &[b'G'; 32]].concat());
+    }
+
+    #[test]
+    fn kmers2seqs() {
+        let kmers = [0b1000111101, 0b1101011000];
+
+        let mut buffer = [0; 10];
+        kmers2seqs_into(&kmers, 5, &mut buffer);
+        assert_eq!(&buffer, b"TAGGCGCCTA");
+
+        let mut out = Vec::new();
+        kmers2seqs_write(&kmers, 5, &mut out).unwrap();
+        assert_eq!(out, b"TAGGC\nGCCTA\n".to_vec());
+    }
+
+    #[test]
+    #[should_panic]
+    fn kmers2seqs_short_buffer() {
+        let mut buffer = [0; 9];
+        kmers2seqs_into(&[0b1000111101, 0b1101011000], 5, &mut buffer);
     }
 
     #[test]
@@ -175,6 +765,42 @@ mod tests {
         assert_eq!(canonical(0b1101011000, 5), 0b1000111101);
     }
 
+    #[test]
+    fn canonical_min_() {
+        // TAGGC -> 1000111101 revcomp GCCTA -> 1101011000
+        assert_eq!(canonical_min(0b1000111101, 5), 0b1000111101);
+        assert_eq!(canonical_min(0b1101011000, 5), 0b1000111101);
+
+        // TAGG -> 10001111 revcomp CCTA -> 01011000
+        assert_eq!(canonical_min(0b10001111, 4), 0b01011000);
+        assert_eq!(canonical_min(0b01011000, 4), 0b01011000);
+
+        // ACGT is is own reverse complement
+        assert_eq!(canonical_min(seq2bit(b"ACGT"), 4), seq2bit(b"ACGT"));
+
+        // parity didn't work for even k
+        assert_ne!(canonical(0b10001111, 4), canonical(0b01011000, 4));
+    }
+
+    #[test]
+    fn canonical_any_() {
+        assert_eq!(canonical_any(0b1101011000, 5), canonical(0b1101011000, 5));
+        assert_eq!(canonical_any(0b10001111, 4), 0b01011000);
+        assert_eq!(canonical_any(0b01011000, 4), 0b01011000);
+        assert_eq!(canonical_any(seq2bit(b"TG"), 2), seq2bit(b"CA"));
+
+        assert_eq!(u16::canonical_any(0b1101011000, 5), 0b1000111101);
+        assert_eq!(u8::canonical_any(0b10001111, 4), 0b01011000);
+    }
+
+    #[test]
+    fn hash_min_() {
+        assert_eq!(hash_min(b"TAGG", 4), 0b01011000);
+        assert_eq!(hash_min(b"CCTA", 4), 0b01011000);
+        assert_eq!(<u16 as Kmer>::hash_min(b"CCTA", 4), 0b01011000);
+        assert_eq!(u16::seq2bit(b"TAGG").canonical_min(4), 0b01011000);
+    }
+
     #[test]
     fn parity_even_() {
         assert!(parity_even(0b1111));
@@ -191,6 +817,8 @@ mod tests {
     fn comp_() {
         // TAGGC -> 1000111101 comp 0001001011
         assert_eq!(comp(0b1000111101, 5), 0b0010010111);
+
+        assert_eq!(comp(0, 32), speed_comp(0));
     }
 
     #[test]
@@ -210,9 +838,168 @@ mod tests {
         assert_eq!(hash(b"GCCTA", 5), 0b100011110);
     }
 
+    #[test]
+    fn add_first_bit_() {
+        // TAGGC -> 1000111101 hash 100011110
+        assert_eq!(add_first_bit(0b100011110), 0b1000111101);
+        assert_eq!(add_first_bit(remove_first_bit(108)), 108);
+    }
+
+    #[test]
+    fn hash2kmer_() {
+        assert_eq!(hash2kmer(hash(b"GCCTA", 5), 5), seq2bit(b"TAGGC"));
+        assert_eq!(hash2seq(hash(b"GCCTA", 5), 5), b"TAGGC".to_vec());
+        assert_eq!(hash2kmer(hash_min(b"TAGG", 4), 4), seq2bit(b"CCTA"));
+        assert_eq!(hash2seq(hash_min(b"TAGG", 4), 4), b"CCTA".to_vec());
+
+        for h in 0..get_hash_space_size(7) {
+            let kmer = hash2kmer(h, 7);
+
+            assert!(parity_even(kmer));
+            assert_eq!(hash(&kmer2seq(kmer, 7), 7), h);
+        }
+
+        assert_eq!(
+            u128::seq2bit(b"TAGGC"),
+            <u128 as Kmer>::hash(b"GCCTA", 5).hash2kmer(5)
+        );
+        assert_eq!(
+            u16::seq2bit(b"CCTA"),
+            <u16 as Kmer>::hash_min(b"TAGG", 4).hash2kmer(4)
+        );
+    }
+
+    #[test]
+    fn hashes2kmers_() {
+        let seq = b"ACTGACTGGATCAATGGAC";
+        let hashes = (0..seq.len() - 4)
+            .map(|i| hash(&seq[i..i + 5], 5))
+            .collect::<Vec<u64>>();
+
+        assert_eq!(
+            hashes2kmers(&hashes, 5),
+            crate::tokenizer::kmer::Canonical::<u64>::new(seq, 5).collect::<Vec<u64>>()
+        );
+    }
+
     #[test]
     fn kmer_space_size() {
         assert_eq!(get_kmer_space_size(5), 1024);
         assert_eq!(get_kmer_space_size(15), 1073741824);
+        assert_eq!(get_kmer_space_size_u128(32), u64::MAX as u128 + 1);
+        assert_eq!(get_kmer_space_size_u128(63), 1 << 126);
+        assert_eq!(get_hash_space_size(32), 1 << 63);
+    }
+
+    #[test]
+    fn mix_() {
+        assert_eq!(mix(0), 0);
+        assert_ne!(mix(1), 1);
+        assert_ne!(mix(1), mix(2));
+
+        for kmer in [
+            0,
+            1,
+            42,
+            seq2bit(b"ACTGACTGGATCAATGGACACGTACGATCGA"),
+            u64::MAX,
+        ] {
+            assert_eq!(unmix(mix(kmer)), kmer);
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn u128_wrapper() {
+        let seq = b"TAGGCGATTCATATAGCGTTACCATGGCATCGATCGATTACGGACTATTCAGGCTACGATTCA";
+        let kmer = u128::seq2bit(seq);
+
+        assert_eq!(seq2bit_u128(seq), kmer);
+        assert_eq!(nuc2bit_u128(b'G'), 0b11);
+        assert_eq!(kmer2seq_u128(kmer, 63), seq.to_vec());
+        assert_eq!(canonical_u128(kmer, 63), kmer.canonical(63));
+        assert_eq!(parity_even_u128(kmer), kmer.parity_even());
+        assert_eq!(revcomp_u128(kmer, 63), kmer.revcomp(63));
+        assert_eq!(comp_u128(kmer, 63), kmer.comp(63));
+        assert_eq!(remove_first_bit_u128(kmer), kmer >> 1);
+        assert_eq!(hash_u128(seq, 63), <u128 as Kmer>::hash(seq, 63));
+        assert_eq!(rev_u128(kmer, 63), Kmer::rev(kmer, 63));
+        assert_eq!(get_kmer_space_size_u128(5), 1024);
+        assert_eq!(get_hash_space_size_u128(63), 1 << 125);
+    }
+
+    #[test]
+    fn trait_same_as_u64() {
+        let seq = b"TAGGCGATTCATATAGCGTTACCAT";
+        let kmer = seq2bit(seq);
+
+        assert_eq!(u64::seq2bit(seq), kmer);
+        assert_eq!(u128::seq2bit(seq), kmer as u128);
+        assert_eq!(Kmer::kmer2seq(kmer, 25), kmer2seq(kmer, 25));
+        assert_eq!(Kmer::rev(kmer, 25), rev(kmer, 25));
+        assert_eq!(Kmer::revcomp(kmer, 25), revcomp(kmer, 25));
+        assert_eq!(Kmer::revcomp(kmer as u128, 25), revcomp(kmer, 25) as u128);
+        assert_eq!(Kmer::canonical(kmer, 25), canonical(kmer, 25));
+        assert_eq!(
+            Kmer::canonical(kmer as u128, 25),
+            canonical(kmer, 25) as u128
+        );
+        assert_eq!(Kmer::comp(kmer, 25), comp(kmer, 25));
+        assert_eq!(<u64 as Kmer>::hash(seq, 25), hash(seq, 25));
+        assert_eq!(<u128 as Kmer>::hash(seq, 25), hash(seq, 25) as u128);
+    }
+
+    #[test]
+    fn trait_small_type() {
+        // TAGGC -> 1000111101
+        assert_eq!(u16::seq2bit(b"TAGGC"), 0b1000111101);
+        assert_eq!(u16::seq2bit(b"GCCTA").revcomp(5), 0b1000111101);
+        assert_eq!(u16::seq2bit(b"GCCTA").canonical(5), 0b1000111101);
+        assert_eq!(<u16 as Kmer>::hash(b"GCCTA", 5), 0b100011110);
+
+        // TAGG -> 10001111 revcomp CCTA -> 01011000
+        assert_eq!(u8::seq2bit(b"TAGG").revcomp(4), 0b01011000);
+        assert_eq!(u8::seq2bit(b"TAGG").kmer2seq(4), b"TAGG".to_vec());
+
+        assert_eq!(
+            u32::seq2bit(b"TAGGCGATTCATATA").kmer2seq(15),
+            b"TAGGCGATTCATATA"
+        );
+        assert_eq!(u32::kmer_mask(16), u32::MAX);
+        assert_eq!(u32::kmer_mask(5), 0b11_1111_1111);
+    }
+
+    #[test]
+    fn trait_large_k() {
+        let seq = b"TAGGCGATTCATATAGCGTTACCATGGCATCGATCGATTACGGACTATTCAGGCTACGATTCA";
+        let revcomp_seq = crate::bytevec::revcomp(seq);
+
+        assert_eq!(u128::seq2bit(seq).kmer2seq(63), seq.to_vec());
+        assert_eq!(u128::seq2bit(seq).revcomp(63), u128::seq2bit(&revcomp_seq));
+        assert_eq!(
+            u128::seq2bit(seq).canonical(63),
+            u128::seq2bit(&revcomp_seq).canonical(63)
+        );
+        assert!(u128::seq2bit(seq).canonical(63).parity_even());
+        assert_eq!(
+            <u128 as Kmer>::hash(seq, 63),
+            <u128 as Kmer>::hash(&revcomp_seq, 63)
+        );
+
+        // k = 64 use all bits of u128
+        let seq = b"TAGGCGATTCATATAGCGTTACCATGGCATCGATCGATTACGGACTATTCAGGCTACGATTCAG";
+        let revcomp_seq = crate::bytevec::revcomp(seq);
+
+        assert_eq!(u128::kmer_mask(64), u128::MAX);
+        assert_eq!(u128::seq2bit(seq).kmer2seq(64), seq.to_vec());
+        assert_eq!(u128::seq2bit(seq).revcomp(64), u128::seq2bit(&revcomp_seq));
+        assert_eq!(
+            u128::seq2bit(seq).comp(64),
+            u128::seq2bit(seq) ^ <u128 as Kmer>::COMP_MASK
+        );
+        assert_eq!(
+            u128::seq2bit(seq).canonical_min(64),
+            u128::seq2bit(&revcomp_seq).canonical_min(64)
+        );
     }
 }
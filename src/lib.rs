@@ -8,6 +8,17 @@
 
 /* module declaration */
 pub mod bytevec;
+pub mod counter;
+pub mod error;
+pub mod fastx;
 pub mod kmer;
+pub mod packed;
 pub mod rle;
+pub mod sketch;
+pub mod spectrum;
 pub mod tokenizer;
+pub mod twobit;
+pub mod typed;
+
+#[cfg(test)]
+pub(crate) mod test_utils;
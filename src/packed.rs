@@ -0,0 +1,369 @@
+//! A DNA sequence store in 2 bit representation, with same encoding as [kmer](crate::kmer) module.
+//!
+//! Each `u64` word store 32 nucleotides, first nucleotide in highest bits, so a [PackedSeq] use 4 times less memory than a `&[u8]`.
+//! Byte that isn't a nucleotide are encoded like in [seq2bit](crate::kmer::seq2bit), use [from_bytes_checked](PackedSeq::from_bytes_checked) to reject them.
+//!
+//! # Example
+//!
+//! ```
+//! use cocktail::packed::PackedSeq;
+//!
+//! let seq = PackedSeq::from_bytes(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA");
+//!
+//! assert_eq!(seq.len(), 50);
+//! assert_eq!(seq.get(3), Some(b'C'));
+//! assert_eq!(seq.kmer_at(3, 5), cocktail::kmer::seq2bit(b"CTGTG"));
+//! assert_eq!(seq.slice(3..8).to_string(), "CTGTG");
+//! assert_eq!(seq.revcomp().revcomp(), seq);
+//! ```
+
+/* standard use */
+use std::ops::{Bound, RangeBounds};
+
+/* crates use */
+
+/* project use */
+use crate::error;
+use crate::kmer;
+
+const NUC_BY_WORD: usize = 32;
+
+/// A DNA sequence store in 2 bit representation
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PackedSeq {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl PackedSeq {
+    /// Create an empty PackedSeq
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty PackedSeq that can store at least capacity nucleotides without reallocation
+    pub fn with_capacity(capacity: usize) -> Self {
+        PackedSeq {
+            len: 0,
+            words: Vec::with_capacity(capacity.div_ceil(NUC_BY_WORD)),
+        }
+    }
+
+    /// Build a PackedSeq from a DNA sequence
+    pub fn from_bytes(seq: &[u8]) -> Self {
+        let mut words = Vec::with_capacity(seq.len().div_ceil(NUC_BY_WORD));
+
+        for chunk in seq.chunks(NUC_BY_WORD) {
+            words.push(kmer::seq2bit(chunk) << (64 - chunk.len() * 2) as u32);
+        }
+
+        PackedSeq {
+            len: seq.len(),
+            words,
+        }
+    }
+
+    /// Same as [from_bytes](PackedSeq::from_bytes) but return an error if seq contains a byte that isn't a nucleotide
+    pub fn from_bytes_checked(seq: &[u8]) -> Result<Self, error::Error> {
+        kmer::validate(seq)?;
+
+        Ok(Self::from_bytes(seq))
+    }
+
+    /// Return the number of nucleotides
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return true if PackedSeq contains no nucleotide
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the 2 bit representation of nucleotide at pos, None if pos is out of sequence
+    #[inline(always)]
+    pub fn get_bits(&self, pos: usize) -> Option<u64> {
+        if pos < self.len {
+            Some(self.bits(pos))
+        } else {
+            None
+        }
+    }
+
+    /// Return nucleotide at pos, None if pos is out of sequence
+    #[inline(always)]
+    pub fn get(&self, pos: usize) -> Option<u8> {
+        self.get_bits(pos).map(kmer::bit2nuc)
+    }
+
+    /// Add a nucleotide at the end of sequence
+    pub fn push(&mut self, nuc: u8) {
+        let offset = self.len % NUC_BY_WORD;
+        if offset == 0 {
+            self.words.push(0);
+        }
+
+        if let Some(word) = self.words.last_mut() {
+            *word |= kmer::nuc2bit(nuc) << (62 - offset * 2);
+        }
+
+        self.len += 1;
+    }
+
+    /// Return the kmer of size k that start at pos in 2 bit representation
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32 or if kmer isn't inside sequence
+    #[inline(always)]
+    pub fn kmer_at(&self, pos: usize, k: u8) -> u64 {
+        assert!(
+            k > 0 && k <= 32,
+            "kmer size {} isn't supported, it must be in 1..=32",
+            k
+        );
+        assert!(
+            pos + k as usize <= self.len,
+            "kmer at {} of size {} isn't inside sequence of length {}",
+            pos,
+            k,
+            self.len
+        );
+
+        let index = pos / NUC_BY_WORD;
+        let offset = (pos % NUC_BY_WORD) * 2;
+
+        let high = (self.words[index] as u128) << 64;
+        let low = self.words.get(index + 1).copied().unwrap_or(0) as u128;
+
+        (((high | low) << offset) >> (128 - k as usize * 2)) as u64
+    }
+
+    /// Return a new PackedSeq that contains nucleotides in range
+    ///
+    /// # Panics
+    ///
+    /// If range isn't inside sequence
+    pub fn slice<R>(&self, range: R) -> Self
+    where
+        R: RangeBounds<usize>,
+    {
+        let begin = match range.start_bound() {
+            Bound::Included(b) => *b,
+            Bound::Excluded(b) => b + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(e) => e + 1,
+            Bound::Excluded(e) => *e,
+            Bound::Unbounded => self.len,
+        };
+
+        assert!(
+            begin <= end && end <= self.len,
+            "range {}..{} isn't inside sequence of length {}",
+            begin,
+            end,
+            self.len
+        );
+
+        let len = end - begin;
+        let mut words = Vec::with_capacity(len.div_ceil(NUC_BY_WORD));
+
+        let mut pos = begin;
+        while pos < end {
+            let k = (end - pos).min(NUC_BY_WORD);
+            words.push(self.kmer_at(pos, k as u8) << (64 - k * 2) as u32);
+            pos += k;
+        }
+
+        PackedSeq { len, words }
+    }
+
+    /// Return the reverse complement of sequence
+    pub fn revcomp(&self) -> Self {
+        let mut words: Vec<u64> = self
+            .words
+            .iter()
+            .rev()
+            .map(|word| kmer::revcomp(*word, 32))
+            .collect();
+
+        // Padding nucleotides of last word are now at the begin of sequence, shift all words to remove them
+        let padding = (self.words.len() * NUC_BY_WORD - self.len) * 2;
+        if padding != 0 {
+            for i in 0..words.len() {
+                let next = words.get(i + 1).copied().unwrap_or(0);
+                words[i] = (words[i] << padding) | (next >> (64 - padding));
+            }
+        }
+
+        PackedSeq {
+            len: self.len,
+            words,
+        }
+    }
+
+    /// Return an iterator on 2 bit representation of each nucleotide
+    pub fn iter_bits(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.len).map(|pos| self.bits(pos))
+    }
+
+    /// Return an iterator on each nucleotide
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        self.iter_bits().map(kmer::bit2nuc)
+    }
+
+    /// Convert PackedSeq in a DNA sequence
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.iter().collect()
+    }
+
+    /// Build a PackedSeq from words, bits after len nucleotides are cleared
+    pub(crate) fn from_words(mut words: Vec<u64>, len: usize) -> Self {
+        words.resize(len.div_ceil(NUC_BY_WORD), 0);
+
+        let padding = (words.len() * NUC_BY_WORD - len) * 2;
+        if let Some(last) = words.last_mut() {
+            *last &= u64::MAX << padding;
+        }
+
+        PackedSeq { len, words }
+    }
+
+    /// Return words that store sequence, 32 nucleotides by word first nucleotide in highest bits
+    pub(crate) fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Return the 2 bit representation of nucleotide at pos without bound check on pos
+    #[inline(always)]
+    pub(crate) fn bits(&self, pos: usize) -> u64 {
+        (self.words[pos / NUC_BY_WORD] >> (62 - (pos % NUC_BY_WORD) * 2)) & 0b11
+    }
+}
+
+impl From<&[u8]> for PackedSeq {
+    fn from(seq: &[u8]) -> Self {
+        Self::from_bytes(seq)
+    }
+}
+
+impl std::fmt::Display for PackedSeq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut buffer = [0; NUC_BY_WORD];
+
+        let mut pos = 0;
+        while pos < self.len {
+            let k = (self.len - pos).min(NUC_BY_WORD);
+            kmer::kmer2seq_into(self.kmer_at(pos, k as u8), k as u8, &mut buffer);
+
+            // buffer contains only A, C, T or G
+            f.write_str(unsafe { std::str::from_utf8_unchecked(&buffer[..k]) })?;
+            pos += k;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /* project use */
+    use super::*;
+    use crate::bytevec;
+    use crate::test_utils::random_seq;
+
+    #[test]
+    fn from_bytes() {
+        let seq = random_seq(150).to_ascii_uppercase();
+
+        for len in [0, 1, 31, 32, 33, 64, 150] {
+            let packed = PackedSeq::from_bytes(&seq[..len]);
+
+            assert_eq!(packed.len(), len);
+            assert_eq!(packed.is_empty(), len == 0);
+            assert_eq!(packed.to_bytes(), seq[..len].to_vec());
+            assert_eq!(packed.to_string().as_bytes(), &seq[..len]);
+        }
+
+        assert_eq!(
+            PackedSeq::from_bytes_checked(b"ACTNG"),
+            Err(error::Error::Nucleotide {
+                byte: b'N',
+                position: 3
+            })
+        );
+    }
+
+    #[test]
+    fn push() {
+        let seq = random_seq(100).to_ascii_uppercase();
+
+        let mut packed = PackedSeq::with_capacity(100);
+        for nuc in seq.iter() {
+            packed.push(*nuc);
+        }
+
+        assert_eq!(packed, PackedSeq::from_bytes(&seq));
+    }
+
+    #[test]
+    fn get() {
+        let seq = random_seq(100).to_ascii_uppercase();
+        let packed = PackedSeq::from_bytes(&seq);
+
+        for (pos, nuc) in seq.iter().enumerate() {
+            assert_eq!(packed.get(pos), Some(*nuc));
+            assert_eq!(packed.get_bits(pos), Some(kmer::nuc2bit(*nuc)));
+        }
+
+        assert_eq!(packed.get(100), None);
+    }
+
+    #[test]
+    fn kmer_at() {
+        let seq = random_seq(100).to_ascii_uppercase();
+        let packed = PackedSeq::from_bytes(&seq);
+
+        for k in [1, 5, 31, 32] {
+            for pos in 0..=(100 - k) {
+                assert_eq!(
+                    packed.kmer_at(pos, k as u8),
+                    kmer::seq2bit(&seq[pos..pos + k])
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn kmer_at_out() {
+        PackedSeq::from_bytes(b"ACTGACTG").kmer_at(4, 5);
+    }
+
+    #[test]
+    fn slice() {
+        let seq = random_seq(100).to_ascii_uppercase();
+        let packed = PackedSeq::from_bytes(&seq);
+
+        assert_eq!(packed.slice(..), packed);
+        assert_eq!(packed.slice(3..3), PackedSeq::new());
+        assert_eq!(packed.slice(3..70), PackedSeq::from_bytes(&seq[3..70]));
+        assert_eq!(packed.slice(40..=99), PackedSeq::from_bytes(&seq[40..]));
+    }
+
+    #[test]
+    fn revcomp() {
+        let seq = random_seq(100).to_ascii_uppercase();
+
+        for len in [0, 1, 31, 32, 33, 64, 100] {
+            assert_eq!(
+                PackedSeq::from_bytes(&seq[..len]).revcomp(),
+                PackedSeq::from_bytes(&bytevec::revcomp(&seq[..len]))
+            );
+        }
+    }
+}
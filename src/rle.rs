@@ -82,6 +82,12 @@ pub fn rle2bit(rle: u8) -> u64 {
     (rle & 0b11) as u64
 }
 
+/// Return the number of nucleotide encode by a rle.
+#[inline(always)]
+pub fn rle2len(rle: u8) -> usize {
+    ((rle >> 2) + 1) as usize
+}
+
 /// Convert a rle in String.
 #[inline(always)]
 pub fn rle2seq(rles: &[u8]) -> String {
@@ -156,6 +162,18 @@ mod tests {
         assert_eq!(rle2bit(seq2rle(b"GGGGGGGGG")[0]), 3);
     }
 
+    #[test]
+    fn rle2len_() {
+        assert_eq!(rle2len(seq2rle(b"A")[0]), 1);
+        assert_eq!(rle2len(seq2rle(b"TTTT")[0]), 4);
+        assert_eq!(
+            rle2len(
+                seq2rle(b"AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA")[0]
+            ),
+            64
+        );
+    }
+
     #[test]
     fn rle2seq_() {
         assert_eq!(rle2seq(&seq2rle(b"ACCGTTAGcATG")), "ACCGTTAGCATG");
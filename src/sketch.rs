@@ -0,0 +1,53 @@
+//! Probabilistic data structures on canonical kmers.
+//!
+//! Raw 2 bit kmers are far from uniform, sketches hash kmers with [mix], an invertible mixing function (murmur3 finalizer).
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+use crate::error;
+
+pub use crate::kmer::{mix, unmix};
+
+/* module declaration */
+pub mod bloom;
+pub mod countmin;
+pub mod hll;
+
+/// Return first hash and step between hash functions of kmer, step is odd
+#[inline(always)]
+pub(crate) fn double_hash(kmer: u64) -> (u64, u64) {
+    let hash = mix(kmer);
+
+    (hash, mix(hash ^ 0x9e37_79b9_7f4a_7c15) | 1)
+}
+
+/// Error of a truncated sketch file
+pub(crate) const TRUNCATED: error::Error = error::Error::SketchFormat {
+    reason: "unexpected end of file",
+};
+
+/// Read len values of N bytes, memory grow with data read so a wrong len in a header can't allocate more than input size
+pub(crate) fn read_values<R, T, const N: usize>(
+    input: &mut R,
+    len: u64,
+    convert: fn([u8; N]) -> T,
+) -> Result<Vec<T>, error::Error>
+where
+    R: std::io::Read,
+{
+    let len = usize::try_from(len).map_err(|_| error::Error::SketchFormat {
+        reason: "sketch is too large",
+    })?;
+
+    let mut values = Vec::with_capacity(len.min(1 << 16));
+    let mut bytes = [0; N];
+    for _ in 0..len {
+        error::read_exact(input, &mut bytes, TRUNCATED)?;
+        values.push(convert(bytes));
+    }
+
+    Ok(values)
+}
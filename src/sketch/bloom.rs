@@ -0,0 +1,348 @@
+//! Bloom filter of canonical kmers, an approximate membership structure without false negative.
+//!
+//! Hash functions are build by double hashing from [mix](super::mix) of canonical kmer.
+//!
+//! # Example
+//!
+//! ```
+//! use cocktail::sketch::bloom::Bloom;
+//!
+//! let mut filter = Bloom::new(11, 1 << 16, 3);
+//! filter.insert_seq(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA");
+//!
+//! assert!(filter.contains(cocktail::kmer::seq2bit(b"GTACTGTGCCC")));
+//! assert_eq!(filter.contains_seq(b"TTACTTAGTAAGCGTG"), 1.0);
+//! ```
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+use crate::error;
+use crate::kmer;
+use crate::sketch;
+use crate::tokenizer;
+
+/// Version of Bloom file
+pub const VERSION: u8 = 1;
+
+const SIGNATURE: [u8; 4] = *b"CKBF";
+
+/// A Bloom filter of canonical kmers of size k
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bloom {
+    k: u8,
+    hashes: u8,
+    size: u64,
+    bits: Vec<u64>,
+}
+
+impl Bloom {
+    /// Create a new empty Bloom filter for kmer of size k, with size bits and hashes hash functions
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32, size or hashes is 0, see [try_new](Bloom::try_new)
+    pub fn new(k: u8, size: u64, hashes: u8) -> Self {
+        Self::try_new(k, size, hashes).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](Bloom::new) but return an error if k isn't in 1..=32, size or hashes is 0
+    pub fn try_new(k: u8, size: u64, hashes: u8) -> Result<Self, error::Error> {
+        Self::check_parameter(k, size, hashes)?;
+
+        Ok(Bloom {
+            k,
+            hashes,
+            size,
+            bits: vec![0; size.div_ceil(64) as usize],
+        })
+    }
+
+    /// Return kmer size
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+
+    /// Return number of bits
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Return number of hash functions
+    pub fn hashes(&self) -> u8 {
+        self.hashes
+    }
+
+    /// Return number of bits set
+    pub fn count_ones(&self) -> u64 {
+        self.bits.iter().map(|word| word.count_ones() as u64).sum()
+    }
+
+    /// Return an estimation of false positive rate with current fill
+    pub fn false_positive_rate(&self) -> f64 {
+        (self.count_ones() as f64 / self.size as f64).powi(self.hashes as i32)
+    }
+
+    /// Insert a kmer in any orientation
+    pub fn insert(&mut self, kmer: u64) {
+        self.insert_canonical(kmer::canonical_any(kmer, self.k));
+    }
+
+    /// Insert all kmers of seq
+    pub fn insert_seq(&mut self, seq: &[u8]) {
+        for kmer in tokenizer::kmer::Canonical::<u64>::new(seq, self.k) {
+            self.insert_canonical(kmer);
+        }
+    }
+
+    /// Return true if kmer in any orientation is probably present, false if it's absent
+    pub fn contains(&self, kmer: u64) -> bool {
+        self.contains_canonical(kmer::canonical_any(kmer, self.k))
+    }
+
+    /// Return fraction of kmers of seq present in filter, 0 if seq is shorter than k
+    pub fn contains_seq(&self, seq: &[u8]) -> f64 {
+        let mut total = 0;
+        let mut present = 0;
+        for kmer in tokenizer::kmer::Canonical::<u64>::new(seq, self.k) {
+            total += 1;
+            if self.contains_canonical(kmer) {
+                present += 1;
+            }
+        }
+
+        if total == 0 {
+            0.0
+        } else {
+            present as f64 / total as f64
+        }
+    }
+
+    /// Add kmers of other in self
+    ///
+    /// # Panics
+    ///
+    /// If k, size or number of hash functions of other isn't equal to self
+    pub fn union(&mut self, other: &Self) {
+        self.check_compatible(other);
+
+        for (word, other) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *word |= other;
+        }
+    }
+
+    /// Keep only kmers present in self and other, false positive rate of result is higher than a filter build with only shared kmers
+    ///
+    /// # Panics
+    ///
+    /// If k, size or number of hash functions of other isn't equal to self
+    pub fn intersection(&mut self, other: &Self) {
+        self.check_compatible(other);
+
+        for (word, other) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *word &= other;
+        }
+    }
+
+    /// Write filter in output
+    pub fn write<W>(&self, mut output: W) -> Result<(), error::Error>
+    where
+        W: std::io::Write,
+    {
+        output.write_all(&SIGNATURE)?;
+        output.write_all(&[VERSION, self.k, self.hashes, 0])?;
+        output.write_all(&self.size.to_le_bytes())?;
+        for word in self.bits.iter() {
+            output.write_all(&word.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Read a filter write by [write](Bloom::write)
+    pub fn read<R>(mut input: R) -> Result<Self, error::Error>
+    where
+        R: std::io::Read,
+    {
+        let mut header = [0; 16];
+        error::read_exact(&mut input, &mut header, sketch::TRUNCATED)?;
+
+        if header[0..4] != SIGNATURE {
+            return Err(error::Error::SketchFormat {
+                reason: "bad bloom filter signature",
+            });
+        }
+        if header[4] != VERSION {
+            return Err(error::Error::SketchFormat {
+                reason: "unsupported bloom filter version",
+            });
+        }
+
+        let mut size = [0; 8];
+        size.copy_from_slice(&header[8..]);
+        let (k, size, hashes) = (header[5], u64::from_le_bytes(size), header[6]);
+        Self::check_parameter(k, size, hashes)?;
+
+        // size isn't trusted, bits are allocate when they are read
+        let bits = sketch::read_values(&mut input, size.div_ceil(64), u64::from_le_bytes)?;
+
+        Ok(Bloom {
+            k,
+            hashes,
+            size,
+            bits,
+        })
+    }
+
+    fn check_parameter(k: u8, size: u64, hashes: u8) -> Result<(), error::Error> {
+        kmer::check_kmer_size(k, 32)?;
+        if size == 0 {
+            return Err(error::Error::SketchParameter {
+                reason: "bloom filter size must be larger than 0",
+            });
+        }
+        if hashes == 0 {
+            return Err(error::Error::SketchParameter {
+                reason: "bloom filter must have at least one hash function",
+            });
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn insert_canonical(&mut self, kmer: u64) {
+        let (mut hash, step) = sketch::double_hash(kmer);
+        for _ in 0..self.hashes {
+            let bit = hash % self.size;
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+            hash = hash.wrapping_add(step);
+        }
+    }
+
+    #[inline(always)]
+    fn contains_canonical(&self, kmer: u64) -> bool {
+        let (mut hash, step) = sketch::double_hash(kmer);
+        for _ in 0..self.hashes {
+            let bit = hash % self.size;
+            if self.bits[(bit / 64) as usize] & (1 << (bit % 64)) == 0 {
+                return false;
+            }
+            hash = hash.wrapping_add(step);
+        }
+
+        true
+    }
+
+    fn check_compatible(&self, other: &Self) {
+        assert!(
+            self.k == other.k && self.size == other.size && self.hashes == other.hashes,
+            "can't combine Bloom filter with different parameters"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /* project use */
+    use super::*;
+    use crate::test_utils::random_seq;
+
+    #[test]
+    fn insert_contains() {
+        let seq = random_seq(1000);
+        let mut filter = Bloom::new(15, 1 << 16, 4);
+        filter.insert_seq(&seq[..500]);
+
+        assert_eq!(filter.contains_seq(&seq[..500]), 1.0);
+        let revcomp = seq[..500]
+            .iter()
+            .rev()
+            .map(|nuc| match nuc.to_ascii_uppercase() {
+                b'A' => b'T',
+                b'C' => b'G',
+                b'T' => b'A',
+                _ => b'C',
+            })
+            .collect::<Vec<u8>>();
+        assert_eq!(filter.contains_seq(&revcomp), 1.0);
+        assert!(filter.contains_seq(&seq[600..]) < 0.05);
+        assert_eq!(filter.contains_seq(b"ACTG"), 0.0);
+        assert!(filter.false_positive_rate() < 0.001);
+
+        let mut other = Bloom::new(15, 1 << 16, 4);
+        other.insert(kmer::seq2bit(&seq[0..15]));
+        assert!(other.contains(kmer::seq2bit(&seq[0..15])));
+        assert_eq!(other.count_ones(), 4);
+    }
+
+    #[test]
+    fn union_intersection() {
+        let seq = random_seq(1000);
+
+        let mut first = Bloom::new(21, 1 << 16, 3);
+        first.insert_seq(&seq[..600]);
+        let mut second = Bloom::new(21, 1 << 16, 3);
+        second.insert_seq(&seq[400..]);
+
+        let mut union = first.clone();
+        union.union(&second);
+        assert_eq!(union.contains_seq(&seq), 1.0);
+
+        let mut intersection = first.clone();
+        intersection.intersection(&second);
+        assert_eq!(intersection.contains_seq(&seq[400..600]), 1.0);
+        assert!(intersection.contains_seq(&seq[..380]) < 0.05);
+    }
+
+    #[test]
+    #[should_panic]
+    fn union_different() {
+        Bloom::new(21, 1 << 16, 3).union(&Bloom::new(21, 1 << 16, 2));
+    }
+
+    #[test]
+    fn parameter() {
+        assert!(Bloom::try_new(33, 10, 1).is_err());
+        assert!(Bloom::try_new(21, 0, 1).is_err());
+        assert!(Bloom::try_new(21, 10, 0).is_err());
+    }
+
+    #[test]
+    fn serialization() {
+        let mut filter = Bloom::new(11, 1000, 3);
+        filter.insert_seq(&random_seq(100));
+
+        let mut file = Vec::new();
+        filter.write(&mut file).unwrap();
+        assert_eq!(file.len(), 16 + 1000usize.div_ceil(64) * 8);
+
+        assert_eq!(Bloom::read(&file[..]).unwrap(), filter);
+        assert_eq!(
+            Bloom::read(&file[..20]),
+            Err(error::Error::SketchFormat {
+                reason: "unexpected end of file"
+            })
+        );
+
+        // a wrong size didn't allocate memory before data is read
+        let mut large = file.clone();
+        large[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            Bloom::read(&large[..]),
+            Err(error::Error::SketchFormat {
+                reason: "unexpected end of file"
+            })
+        );
+
+        file[0] = b'X';
+        assert_eq!(
+            Bloom::read(&file[..]),
+            Err(error::Error::SketchFormat {
+                reason: "bad bloom filter signature"
+            })
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Count-min sketch of canonical kmers, an approximate count table that never underestimate.
+//!
+//! Sketch contains depth rows of width counters, each kmer increment one counter by row and its count is the minimum of its counters.
+//! With conservative update only counters equal to the minimum are incremented, it reduce overestimation but sketch can't be decrement.
+//!
+//! Sketch build on different threads can be merged, count of merged sketch is still an upper bound.
+//!
+//! # Example
+//!
+//! ```
+//! use cocktail::sketch::countmin::CountMin;
+//!
+//! let mut sketch = CountMin::new(5, 1 << 10, 4, true);
+//! sketch.add_seq(b"ACTGACTGACTG");
+//!
+//! assert!(sketch.query(cocktail::kmer::seq2bit(b"ACTGA")) >= 2);
+//!
+//! // keep only kmers seen at least twice
+//! let solid = cocktail::tokenizer::kmer::Canonical::<u64>::new(b"ACTGACTGACTG", 5)
+//!     .filter(|kmer| sketch.query(*kmer) >= 2)
+//!     .collect::<Vec<u64>>();
+//! ```
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+use crate::error;
+use crate::kmer;
+use crate::sketch;
+use crate::tokenizer;
+
+/// Version of CountMin file
+pub const VERSION: u8 = 1;
+
+const SIGNATURE: [u8; 4] = *b"CKCM";
+
+/// A count-min sketch of canonical kmers of size k
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountMin {
+    k: u8,
+    depth: u8,
+    width: u64,
+    conservative: bool,
+    counts: Vec<u32>,
+}
+
+impl CountMin {
+    /// Create a new empty CountMin for kmer of size k, with depth rows of width counters, if conservative is true counters are update conservatively
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32, width or depth is 0, see [try_new](CountMin::try_new)
+    pub fn new(k: u8, width: u64, depth: u8, conservative: bool) -> Self {
+        Self::try_new(k, width, depth, conservative).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](CountMin::new) but return an error if k isn't in 1..=32, width or depth is 0
+    pub fn try_new(k: u8, width: u64, depth: u8, conservative: bool) -> Result<Self, error::Error> {
+        let len = Self::check_parameter(k, width, depth)?;
+
+        Ok(CountMin {
+            k,
+            depth,
+            width,
+            conservative,
+            counts: vec![0; len],
+        })
+    }
+
+    /// Return kmer size
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+
+    /// Return number of counters by row
+    pub fn width(&self) -> u64 {
+        self.width
+    }
+
+    /// Return number of rows
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Return true if counters are update conservatively
+    pub fn conservative(&self) -> bool {
+        self.conservative
+    }
+
+    /// Increment count of kmer, kmer can be in any orientation
+    pub fn add(&mut self, kmer: u64) {
+        self.add_canonical(kmer::canonical_any(kmer, self.k));
+    }
+
+    /// Increment count of all kmers of seq
+    pub fn add_seq(&mut self, seq: &[u8]) {
+        for kmer in tokenizer::kmer::Canonical::<u64>::new(seq, self.k) {
+            self.add_canonical(kmer);
+        }
+    }
+
+    /// Return an upper bound of kmer count, kmer can be in any orientation
+    pub fn query(&self, kmer: u64) -> u32 {
+        let kmer = kmer::canonical_any(kmer, self.k);
+
+        self.indexes(kmer)
+            .map(|index| self.counts[index])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Add counts of other in self
+    ///
+    /// # Panics
+    ///
+    /// If k, width or depth of other isn't equal to self
+    pub fn merge(&mut self, other: &Self) {
+        assert!(
+            self.k == other.k && self.width == other.width && self.depth == other.depth,
+            "can't merge CountMin with different parameters"
+        );
+
+        for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count = count.saturating_add(*other);
+        }
+    }
+
+    /// Write sketch in output
+    pub fn write<W>(&self, mut output: W) -> Result<(), error::Error>
+    where
+        W: std::io::Write,
+    {
+        output.write_all(&SIGNATURE)?;
+        output.write_all(&[VERSION, self.k, self.depth, self.conservative as u8])?;
+        output.write_all(&self.width.to_le_bytes())?;
+        for count in self.counts.iter() {
+            output.write_all(&count.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Read a sketch write by [write](CountMin::write)
+    pub fn read<R>(mut input: R) -> Result<Self, error::Error>
+    where
+        R: std::io::Read,
+    {
+        let mut header = [0; 16];
+        error::read_exact(&mut input, &mut header, sketch::TRUNCATED)?;
+
+        if header[0..4] != SIGNATURE {
+            return Err(error::Error::SketchFormat {
+                reason: "bad count-min signature",
+            });
+        }
+        if header[4] != VERSION {
+            return Err(error::Error::SketchFormat {
+                reason: "unsupported count-min version",
+            });
+        }
+
+        let mut width = [0; 8];
+        width.copy_from_slice(&header[8..]);
+        let (k, width, depth) = (header[5], u64::from_le_bytes(width), header[6]);
+        let len = Self::check_parameter(k, width, depth)?;
+
+        // width isn't trusted, counters are allocate when they are read
+        let counts = sketch::read_values(&mut input, len as u64, u32::from_le_bytes)?;
+
+        Ok(CountMin {
+            k,
+            depth,
+            width,
+            conservative: header[7] != 0,
+            counts,
+        })
+    }
+
+    /// Check parameters and return number of counters
+    fn check_parameter(k: u8, width: u64, depth: u8) -> Result<usize, error::Error> {
+        kmer::check_kmer_size(k, 32)?;
+        if width == 0 {
+            return Err(error::Error::SketchParameter {
+                reason: "count-min width must be larger than 0",
+            });
+        }
+        if depth == 0 {
+            return Err(error::Error::SketchParameter {
+                reason: "count-min depth must be larger than 0",
+            });
+        }
+
+        width
+            .checked_mul(depth as u64)
+            .and_then(|len| usize::try_from(len).ok())
+            .ok_or(error::Error::SketchParameter {
+                reason: "count-min width * depth is too large",
+            })
+    }
+
+    #[inline(always)]
+    fn add_canonical(&mut self, kmer: u64) {
+        if self.conservative {
+            let min = self
+                .indexes(kmer)
+                .map(|index| self.counts[index])
+                .min()
+                .unwrap_or(0)
+                .saturating_add(1);
+
+            for index in self.indexes(kmer) {
+                self.counts[index] = self.counts[index].max(min);
+            }
+        } else {
+            for index in self.indexes(kmer) {
+                self.counts[index] = self.counts[index].saturating_add(1);
+            }
+        }
+    }
+
+    /// Return index of kmer counter in each row
+    #[inline(always)]
+    fn indexes(&self, kmer: u64) -> impl Iterator<Item = usize> {
+        let (hash, step) = sketch::double_hash(kmer);
+        let width = self.width;
+
+        (0..self.depth as u64).map(move |row| {
+            (row * width + hash.wrapping_add(row.wrapping_mul(step)) % width) as usize
+        })
+    }
+}
+
+impl Extend<u64> for CountMin {
+    fn extend<I>(&mut self, kmers: I)
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        for kmer in kmers {
+            self.add(kmer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /* project use */
+    use super::*;
+    use crate::counter::SparseCounter;
+    use crate::test_utils::random_seq;
+
+    #[test]
+    fn upper_bound() {
+        let seq = random_seq(2000);
+        let mut exact = SparseCounter::new(11);
+        exact.extend(tokenizer::kmer::Canonical::<u64>::new(&seq, 11));
+        exact.extend(tokenizer::kmer::Canonical::<u64>::new(&seq[..500], 11));
+
+        for conservative in [false, true] {
+            let mut sketch = CountMin::new(11, 1 << 14, 4, conservative);
+            sketch.add_seq(&seq);
+            sketch.add_seq(&seq[..500]);
+
+            let mut error = 0;
+            for (kmer, count) in exact.iter() {
+                assert!(sketch.query(kmer) >= count);
+                assert_eq!(sketch.query(kmer), sketch.query(kmer::revcomp(kmer, 11)));
+                error += sketch.query(kmer) - count;
+            }
+
+            // with this load overestimation is rare
+            assert!(error < exact.len() as u32);
+        }
+    }
+
+    #[test]
+    fn conservative() {
+        let seq = random_seq(2000);
+
+        let mut classic = CountMin::new(15, 256, 3, false);
+        classic.add_seq(&seq);
+        let mut conservative = CountMin::new(15, 256, 3, true);
+        conservative.add_seq(&seq);
+
+        let sum = |sketch: &CountMin| {
+            tokenizer::kmer::Canonical::<u64>::new(&seq, 15)
+                .map(|kmer| sketch.query(kmer) as u64)
+                .sum::<u64>()
+        };
+        assert!(sum(&conservative) <= sum(&classic));
+    }
+
+    #[test]
+    fn merge() {
+        let seq = random_seq(1000);
+
+        let sketches = std::thread::scope(|scope| {
+            let handles = seq
+                .chunks(250)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        let mut sketch = CountMin::new(9, 1 << 12, 4, false);
+                        sketch.add_seq(chunk);
+                        sketch
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<CountMin>>()
+        });
+
+        let mut merged = CountMin::new(9, 1 << 12, 4, false);
+        for sketch in sketches.iter() {
+            merged.merge(sketch);
+        }
+
+        let mut expected = CountMin::new(9, 1 << 12, 4, false);
+        for chunk in seq.chunks(250) {
+            expected.add_seq(chunk);
+        }
+
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn parameter() {
+        assert!(CountMin::try_new(33, 10, 1, false).is_err());
+        assert!(CountMin::try_new(21, 0, 1, false).is_err());
+        assert!(CountMin::try_new(21, 10, 0, true).is_err());
+        assert_eq!(
+            CountMin::try_new(21, u64::MAX, 2, true),
+            Err(error::Error::SketchParameter {
+                reason: "count-min width * depth is too large"
+            })
+        );
+    }
+
+    #[test]
+    fn serialization() {
+        let mut sketch = CountMin::new(11, 100, 3, true);
+        sketch.add_seq(&random_seq(200));
+
+        let mut file = Vec::new();
+        sketch.write(&mut file).unwrap();
+        assert_eq!(file.len(), 16 + 300 * 4);
+
+        assert_eq!(CountMin::read(&file[..]).unwrap(), sketch);
+
+        // a wrong width didn't allocate memory before data is read
+        let mut large = file.clone();
+        large[8..16].copy_from_slice(&(u64::MAX / 4).to_le_bytes());
+        assert_eq!(
+            CountMin::read(&large[..]),
+            Err(error::Error::SketchFormat {
+                reason: "unexpected end of file"
+            })
+        );
+
+        file[4] = 2;
+        assert_eq!(
+            CountMin::read(&file[..]),
+            Err(error::Error::SketchFormat {
+                reason: "unsupported count-min version"
+            })
+        );
+    }
+}
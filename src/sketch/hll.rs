@@ -0,0 +1,314 @@
+//! HyperLogLog estimation of the number of distinct canonical kmers.
+//!
+//! Canonical kmers are hashed with [mix](super::mix), the first precision bits of hash select a register, register keep the largest number of leading zeros of the remaining bits plus one.
+//! With 2^precision registers relative standard error is around 1.04 / sqrt(2^precision).
+//!
+//! # Example
+//!
+//! ```
+//! use cocktail::sketch::hll::{estimate_distinct, HyperLogLog};
+//!
+//! let seq = b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA";
+//!
+//! let mut sketch = HyperLogLog::new(11, 12);
+//! sketch.add_seq(seq);
+//! let distinct = sketch.estimate();
+//!
+//! assert_eq!(estimate_distinct(seq, 11), HyperLogLog::from_seq(seq, 11).estimate());
+//! ```
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+use crate::error;
+use crate::kmer;
+use crate::sketch;
+use crate::tokenizer;
+
+/// Version of HyperLogLog file
+pub const VERSION: u8 = 1;
+
+/// Precision used by [estimate_distinct], 2^14 registers and relative error around 0.8%
+pub const DEFAULT_PRECISION: u8 = 14;
+
+/// Smallest supported precision
+pub const MIN_PRECISION: u8 = 4;
+
+/// Largest supported precision
+pub const MAX_PRECISION: u8 = 18;
+
+const SIGNATURE: [u8; 4] = *b"CKHL";
+
+/// A HyperLogLog sketch of canonical kmers of size k
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyperLogLog {
+    k: u8,
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// Create a new empty HyperLogLog for kmer of size k with 2^precision registers
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32 or precision isn't in [MIN_PRECISION]..=[MAX_PRECISION], see [try_new](HyperLogLog::try_new)
+    pub fn new(k: u8, precision: u8) -> Self {
+        Self::try_new(k, precision).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](HyperLogLog::new) but return an error if k isn't in 1..=32 or precision isn't in [MIN_PRECISION]..=[MAX_PRECISION]
+    pub fn try_new(k: u8, precision: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, 32)?;
+        if !(MIN_PRECISION..=MAX_PRECISION).contains(&precision) {
+            return Err(error::Error::SketchParameter {
+                reason: "hyperloglog precision must be in 4..=18",
+            });
+        }
+
+        Ok(HyperLogLog {
+            k,
+            precision,
+            registers: vec![0; 1 << precision],
+        })
+    }
+
+    /// Build a HyperLogLog with [DEFAULT_PRECISION] from kmers of seq
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32
+    pub fn from_seq(seq: &[u8], k: u8) -> Self {
+        let mut sketch = Self::new(k, DEFAULT_PRECISION);
+        sketch.add_seq(seq);
+
+        sketch
+    }
+
+    /// Return kmer size
+    pub fn k(&self) -> u8 {
+        self.k
+    }
+
+    /// Return precision
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// Add a kmer in any orientation
+    pub fn add(&mut self, kmer: u64) {
+        self.add_canonical(kmer::canonical_any(kmer, self.k));
+    }
+
+    /// Add all kmers of seq
+    pub fn add_seq(&mut self, seq: &[u8]) {
+        for kmer in tokenizer::kmer::Canonical::<u64>::new(seq, self.k) {
+            self.add_canonical(kmer);
+        }
+    }
+
+    /// Return an estimation of the number of distinct canonical kmers added
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+
+        let sum = self
+            .registers
+            .iter()
+            .map(|rank| (-(*rank as f64)).exp2())
+            .sum::<f64>();
+        let raw = alpha * m * m / sum;
+
+        // small cardinality, linear counting is more accurate
+        let zeros = self.registers.iter().filter(|rank| **rank == 0).count();
+        if raw <= 2.5 * m && zeros != 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        }
+    }
+
+    /// Add kmers of other in self
+    ///
+    /// # Panics
+    ///
+    /// If k or precision of other isn't equal to self
+    pub fn merge(&mut self, other: &Self) {
+        assert!(
+            self.k == other.k && self.precision == other.precision,
+            "can't merge HyperLogLog with different parameters"
+        );
+
+        for (rank, other) in self.registers.iter_mut().zip(other.registers.iter()) {
+            *rank = (*rank).max(*other);
+        }
+    }
+
+    /// Write sketch in output
+    pub fn write<W>(&self, mut output: W) -> Result<(), error::Error>
+    where
+        W: std::io::Write,
+    {
+        output.write_all(&SIGNATURE)?;
+        output.write_all(&[VERSION, self.k, self.precision, 0])?;
+        output.write_all(&self.registers)?;
+
+        Ok(())
+    }
+
+    /// Read a sketch write by [write](HyperLogLog::write)
+    pub fn read<R>(mut input: R) -> Result<Self, error::Error>
+    where
+        R: std::io::Read,
+    {
+        let mut header = [0; 8];
+        error::read_exact(&mut input, &mut header, sketch::TRUNCATED)?;
+
+        if header[0..4] != SIGNATURE {
+            return Err(error::Error::SketchFormat {
+                reason: "bad hyperloglog signature",
+            });
+        }
+        if header[4] != VERSION {
+            return Err(error::Error::SketchFormat {
+                reason: "unsupported hyperloglog version",
+            });
+        }
+
+        let mut sketch = HyperLogLog::try_new(header[5], header[6])?;
+        error::read_exact(&mut input, &mut sketch.registers, sketch::TRUNCATED)?;
+
+        if sketch
+            .registers
+            .iter()
+            .any(|rank| *rank > 64 - sketch.precision + 1)
+        {
+            return Err(error::Error::SketchFormat {
+                reason: "hyperloglog register is too large",
+            });
+        }
+
+        Ok(sketch)
+    }
+
+    #[inline(always)]
+    fn add_canonical(&mut self, kmer: u64) {
+        let hash = sketch::mix(kmer);
+
+        let index = (hash >> (64 - self.precision)) as usize;
+        let rank =
+            ((hash << self.precision).leading_zeros() as u8 + 1).min(64 - self.precision + 1);
+
+        self.registers[index] = self.registers[index].max(rank);
+    }
+}
+
+impl Extend<u64> for HyperLogLog {
+    fn extend<I>(&mut self, kmers: I)
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        for kmer in kmers {
+            self.add(kmer);
+        }
+    }
+}
+
+/// Estimate number of distinct canonical kmers of size k in seq, with [DEFAULT_PRECISION]
+///
+/// # Panics
+///
+/// If k isn't in 1..=32
+pub fn estimate_distinct(seq: &[u8], k: u8) -> f64 {
+    HyperLogLog::from_seq(seq, k).estimate()
+}
+
+#[cfg(test)]
+mod tests {
+    /* project use */
+    use super::*;
+    use crate::counter::SparseCounter;
+    use crate::test_utils::random_seq;
+
+    fn exact(seq: &[u8], k: u8) -> f64 {
+        let mut counter = SparseCounter::new(k);
+        counter.extend(tokenizer::kmer::Canonical::<u64>::new(seq, k));
+
+        counter.len() as f64
+    }
+
+    #[test]
+    fn estimate() {
+        let seq = random_seq(100_000);
+
+        for k in [15, 16, 31] {
+            let error = estimate_distinct(&seq, k) / exact(&seq, k) - 1.0;
+            assert!(error.abs() < 0.03);
+        }
+
+        // small cardinality
+        let error = estimate_distinct(&seq[..200], 21) / exact(&seq[..200], 21) - 1.0;
+        assert!(error.abs() < 0.03);
+
+        assert_eq!(HyperLogLog::new(21, 10).estimate(), 0.0);
+    }
+
+    #[test]
+    fn orientation() {
+        let mut forward = HyperLogLog::new(5, 4);
+        forward.add(kmer::seq2bit(b"ACTGA"));
+        let mut reverse = HyperLogLog::new(5, 4);
+        reverse.extend([kmer::revcomp(kmer::seq2bit(b"ACTGA"), 5)]);
+
+        assert_eq!(forward, reverse);
+    }
+
+    #[test]
+    fn merge() {
+        let seq = random_seq(20_000);
+
+        let mut first = HyperLogLog::new(21, 12);
+        first.add_seq(&seq[..12_000]);
+        let mut second = HyperLogLog::new(21, 12);
+        second.add_seq(&seq[8_000..]);
+        first.merge(&second);
+
+        let mut whole = HyperLogLog::new(21, 12);
+        whole.add_seq(&seq);
+
+        assert_eq!(first, whole);
+    }
+
+    #[test]
+    fn parameter() {
+        assert!(HyperLogLog::try_new(33, 10).is_err());
+        assert!(HyperLogLog::try_new(21, 3).is_err());
+        assert!(HyperLogLog::try_new(21, 19).is_err());
+    }
+
+    #[test]
+    fn serialization() {
+        let sketch = HyperLogLog::from_seq(&random_seq(1000), 11);
+
+        let mut file = Vec::new();
+        sketch.write(&mut file).unwrap();
+        assert_eq!(file.len(), 8 + (1 << DEFAULT_PRECISION));
+
+        assert_eq!(HyperLogLog::read(&file[..]).unwrap(), sketch);
+
+        file[8] = 255;
+        assert_eq!(
+            HyperLogLog::read(&file[..]),
+            Err(error::Error::SketchFormat {
+                reason: "hyperloglog register is too large"
+            })
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Kmer spectrum, the number of distinct kmers for each abundance.
+//!
+//! Spectrum can be build from a count table ([DenseCounter], [SparseCounter] or a [format::Reader]) or directly from a stream of kmers.
+//!
+//! In a spectrum of sequencing reads, kmers with erroneous nucleotides produce a high number of low abundance kmers, kmers of genome produce a peak around coverage.
+//! [valley](Histogram::valley) detect the first local minimum between this two distributions and can be used as solid kmer threshold.
+//!
+//! # Example
+//!
+//! ```
+//! use cocktail::spectrum::Histogram;
+//! use cocktail::tokenizer::kmer::Canonical;
+//!
+//! let spectrum = Histogram::from_kmers(Canonical::<u64>::new(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA", 5), 5);
+//!
+//! let threshold = spectrum.valley();
+//! let coverage = spectrum.peak();
+//!
+//! let mut tsv = Vec::new();
+//! spectrum.write_tsv(&mut tsv).unwrap();
+//! ```
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+use crate::counter::{format, Count, DenseCounter, SparseCounter};
+use crate::error;
+
+/* module declaration */
+pub mod model;
+
+/// Largest abundance store in [Histogram], kmers with a larger abundance are count in this abundance
+pub const MAX_ABUNDANCE: u32 = 10_000;
+
+/// Number of distinct kmers for each abundance
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// Create an empty Histogram
+    pub fn new() -> Self {
+        Histogram::default()
+    }
+
+    /// Add a kmer with abundance count, count 0 is ignored and count larger than [MAX_ABUNDANCE] are saturated
+    pub fn add(&mut self, count: u32) {
+        if count == 0 {
+            return;
+        }
+
+        let count = count.min(MAX_ABUNDANCE) as usize;
+        if self.counts.len() <= count {
+            self.counts.resize(count + 1, 0);
+        }
+        self.counts[count] += 1;
+    }
+
+    /// Build Histogram from kmers in any orientation, kmers are count in a [SparseCounter]
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32
+    pub fn from_kmers<I>(kmers: I, k: u8) -> Self
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        let mut counter = SparseCounter::new(k);
+        counter.extend(kmers);
+
+        Self::from_sparse(&counter)
+    }
+
+    /// Build Histogram from a [DenseCounter]
+    pub fn from_dense<C>(counter: &DenseCounter<C>) -> Self
+    where
+        C: Count,
+    {
+        counter.iter().map(|(_, count)| count.to_u32()).collect()
+    }
+
+    /// Build Histogram from a [SparseCounter]
+    pub fn from_sparse(counter: &SparseCounter) -> Self {
+        counter.iter().map(|(_, count)| count).collect()
+    }
+
+    /// Build Histogram from a count table file
+    pub fn from_reader<R>(reader: format::Reader<R>) -> Result<Self, error::Error>
+    where
+        R: std::io::BufRead,
+    {
+        let mut histogram = Histogram::new();
+        for result in reader {
+            histogram.add(result?.1);
+        }
+
+        Ok(histogram)
+    }
+
+    /// Return number of distinct kmers with abundance count
+    pub fn get(&self, count: u32) -> u64 {
+        self.counts.get(count as usize).copied().unwrap_or(0)
+    }
+
+    /// Return largest abundance with at least one kmer, 0 if Histogram is empty
+    pub fn max_abundance(&self) -> u32 {
+        self.counts.iter().rposition(|nb| *nb != 0).unwrap_or(0) as u32
+    }
+
+    /// Return number of distinct kmers
+    pub fn distinct(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Return number of kmers, sum of abundance
+    pub fn total(&self) -> u64 {
+        self.iter().map(|(count, nb)| count as u64 * nb).sum()
+    }
+
+    /// Return an iterator on abundance and number of distinct kmers, abundance without kmer are skipped
+    pub fn iter(&self) -> impl Iterator<Item = (u32, u64)> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, nb)| **nb != 0)
+            .map(|(count, nb)| (count as u32, *nb))
+    }
+
+    /// Add other in self
+    pub fn merge(&mut self, other: &Self) {
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+
+        for (nb, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *nb += other;
+        }
+    }
+
+    /// Return the first local minimum of spectrum, kmers with a lower abundance are probably erroneous
+    ///
+    /// Spectrum is smoothed by a moving average on 3 abundances, so a small noise in error tail isn't detected as a valley.
+    /// Return None if smoothed number of kmers never increase after abundance 1.
+    pub fn valley(&self) -> Option<u32> {
+        let max = self.max_abundance();
+
+        (1..max).find(|count| self.smoothed(count + 1, max) > self.smoothed(*count, max))
+    }
+
+    /// Return abundance with the largest number of kmers after [valley](Histogram::valley), an estimation of coverage
+    ///
+    /// If spectrum hasn't a valley, all abundances are considered.
+    pub fn peak(&self) -> Option<u32> {
+        let start = self.valley().unwrap_or(1);
+
+        (start..=self.max_abundance())
+            .filter(|count| self.get(*count) != 0)
+            .max_by_key(|count| (self.get(*count), std::cmp::Reverse(*count)))
+    }
+
+    /// Mean number of kmers of abundance count and its neighbors in 1..=max
+    fn smoothed(&self, count: u32, max: u32) -> f64 {
+        let range = count.saturating_sub(1).max(1)..=(count + 1).min(max);
+        let len = range.clone().count() as f64;
+
+        range.map(|c| self.get(c)).sum::<u64>() as f64 / len
+    }
+
+    /// Write Histogram in TSV, one line by abundance with `abundance\tnumber of kmers`, abundance without kmer are skipped, same format as `jellyfish histo`
+    pub fn write_tsv<W>(&self, mut output: W) -> Result<(), error::Error>
+    where
+        W: std::io::Write,
+    {
+        for (count, nb) in self.iter() {
+            writeln!(output, "{}\t{}", count, nb)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromIterator<u32> for Histogram {
+    fn from_iter<I>(counts: I) -> Self
+    where
+        I: IntoIterator<Item = u32>,
+    {
+        let mut histogram = Histogram::new();
+        histogram.extend(counts);
+
+        histogram
+    }
+}
+
+impl Extend<u32> for Histogram {
+    fn extend<I>(&mut self, counts: I)
+    where
+        I: IntoIterator<Item = u32>,
+    {
+        for count in counts {
+            self.add(count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer;
+
+    fn spectrum() -> Histogram {
+        // errors, a valley at 4 and a peak at 7
+        let mut histogram = Histogram::new();
+        for (count, nb) in [
+            (1, 100),
+            (2, 30),
+            (3, 10),
+            (4, 5),
+            (5, 8),
+            (6, 20),
+            (7, 25),
+            (8, 18),
+            (9, 6),
+        ] {
+            histogram.extend(std::iter::repeat_n(count, nb));
+        }
+
+        histogram
+    }
+
+    #[test]
+    fn add() {
+        let mut histogram = Histogram::new();
+        histogram.extend([0, 1, 1, 3, u32::MAX]);
+
+        assert_eq!(histogram.get(0), 0);
+        assert_eq!(histogram.get(1), 2);
+        assert_eq!(histogram.get(2), 0);
+        assert_eq!(histogram.get(3), 1);
+        assert_eq!(histogram.get(MAX_ABUNDANCE), 1);
+        assert_eq!(histogram.max_abundance(), MAX_ABUNDANCE);
+        assert_eq!(histogram.distinct(), 4);
+        assert_eq!(histogram.total(), 5 + MAX_ABUNDANCE as u64);
+    }
+
+    #[test]
+    fn valley_peak() {
+        let histogram = spectrum();
+
+        assert_eq!(histogram.valley(), Some(4));
+        assert_eq!(histogram.peak(), Some(7));
+
+        // small increase in error tail isn't a valley
+        let mut noisy = Histogram::new();
+        for (count, nb) in [
+            (1, 100),
+            (2, 30),
+            (3, 10),
+            (4, 11),
+            (5, 9),
+            (6, 5),
+            (7, 8),
+            (8, 20),
+            (9, 25),
+            (10, 18),
+            (11, 6),
+        ] {
+            noisy.extend(std::iter::repeat_n(count, nb));
+        }
+        assert_eq!(noisy.valley(), Some(6));
+        assert_eq!(noisy.peak(), Some(9));
+
+        let decrease: Histogram = [1, 1, 1, 2, 3].into_iter().collect();
+        assert_eq!(decrease.valley(), None);
+        assert_eq!(decrease.peak(), Some(1));
+
+        assert_eq!(Histogram::new().valley(), None);
+        assert_eq!(Histogram::new().peak(), None);
+    }
+
+    #[test]
+    fn from_table() {
+        let seq = b"ACTGACTGACTGGTACGATCGATCAGTACTG";
+
+        let mut dense = DenseCounter::<u8>::new(5);
+        dense.add_seq(seq);
+        let mut sparse = SparseCounter::new(5);
+        sparse.extend(tokenizer::kmer::Canonical::<u64>::new(seq, 5));
+
+        let mut file = Vec::new();
+        format::write_sparse(&mut file, &sparse).unwrap();
+
+        let histogram = Histogram::from_kmers(tokenizer::kmer::Canonical::<u64>::new(seq, 5), 5);
+        assert_eq!(histogram.get(3), 2);
+        assert_eq!(histogram.total(), (seq.len() - 4) as u64);
+        assert_eq!(Histogram::from_dense(&dense), histogram);
+        assert_eq!(Histogram::from_sparse(&sparse), histogram);
+        assert_eq!(
+            Histogram::from_reader(format::Reader::new(&file[..]).unwrap()).unwrap(),
+            histogram
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let mut histogram: Histogram = [1, 2].into_iter().collect();
+        histogram.merge(&[2, 2, 5].into_iter().collect());
+
+        assert_eq!(
+            histogram.iter().collect::<Vec<(u32, u64)>>(),
+            vec![(1, 1), (2, 3), (5, 1)]
+        );
+    }
+
+    #[test]
+    fn tsv() {
+        let mut output = Vec::new();
+        spectrum().write_tsv(&mut output).unwrap();
+
+        assert_eq!(
+            output,
+            b"1\t100\n2\t30\n3\t10\n4\t5\n5\t8\n6\t20\n7\t25\n8\t18\n9\t6\n"
+        );
+    }
+}
@@ -0,0 +1,385 @@
+//! Estimate genome size, heterozygosity, repeat fraction and error rate from a kmer spectrum of a diploid genome, like GenomeScope.
+//!
+//! Spectrum must be build with canonical kmers. Kmers covering an heterozygous site are present in one haplotype and have a coverage λ, other kmers have a coverage 2λ.
+//! With heterozygosity r, a kmer is heterozygous with probability 1 - (1 - r)^k. A fraction d of genome is duplicated, it double coverage of this kmers.
+//! Each peak is a negative binomial with mean μ and variance μ(1 + bias).
+//!
+//! Parameters are fit with Nelder-Mead on abundance after [valley](super::Histogram::valley), kmers not explained by model are considered erroneous.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use cocktail::spectrum::Histogram;
+//! use cocktail::spectrum::model::Model;
+//!
+//! # let reads: Vec<Vec<u8>> = vec![];
+//! let mut spectrum = Histogram::new();
+//! for read in reads {
+//!     spectrum.merge(&Histogram::from_kmers(cocktail::tokenizer::kmer::Canonical::<u64>::new(&read, 21), 21));
+//! }
+//!
+//! if let Some(model) = Model::fit(&spectrum, 21) {
+//!     println!("genome size {}, heterozygosity {}", model.genome_size, model.heterozygosity);
+//! }
+//! ```
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+use crate::spectrum::Histogram;
+
+const MAX_ITERATION: usize = 2000;
+
+/// A fitted model of kmer spectrum
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Model {
+    /// Kmer size
+    pub k: u8,
+
+    /// Kmer coverage of one haplotype (λ)
+    pub coverage: f64,
+
+    /// Heterozygosity, fraction of nucleotides that differ between haplotypes
+    pub heterozygosity: f64,
+
+    /// Fraction of genome that is duplicated
+    pub repeat: f64,
+
+    /// Overdispersion of coverage, variance of a peak with mean μ is μ(1 + bias)
+    pub bias: f64,
+
+    /// Number of distinct kmers explained by model, scale of model
+    pub length: f64,
+
+    /// Haploid genome size estimation
+    pub genome_size: f64,
+
+    /// Error rate by nucleotide estimation
+    pub error_rate: f64,
+}
+
+impl Model {
+    /// Fit model on histogram build with kmers of size k, return None if spectrum hasn't a peak
+    pub fn fit(histogram: &Histogram, k: u8) -> Option<Self> {
+        let peak = histogram.peak()? as f64;
+        let start = histogram.valley().unwrap_or(1);
+        let end = histogram
+            .max_abundance()
+            .min((peak as u32).saturating_mul(10).max(start + 1));
+
+        let observed = (start..=end)
+            .map(|x| (x, histogram.get(x) as f64))
+            .collect::<Vec<(u32, f64)>>();
+
+        // peak could be the heterozygous or homozygous peak
+        let (params, _) = [peak, peak / 2.0]
+            .into_iter()
+            .map(|coverage| {
+                nelder_mead(
+                    |params| objective(params, k, &observed).0,
+                    [coverage, 0.005, 0.01, 0.5],
+                    [coverage * 0.1, 0.005, 0.01, 0.2],
+                )
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+        let (rss, length) = objective(&params, k, &observed);
+        if !rss.is_finite() {
+            return None;
+        }
+
+        let mut model = Model {
+            k,
+            coverage: params[0],
+            heterozygosity: params[1],
+            repeat: params[2],
+            bias: params[3],
+            length,
+            genome_size: 0.0,
+            error_rate: 0.0,
+        };
+
+        let solid = histogram
+            .iter()
+            .filter(|(x, _)| *x >= start)
+            .map(|(x, nb)| x as f64 * nb as f64)
+            .sum::<f64>();
+        model.genome_size = solid / (2.0 * model.coverage);
+
+        let total = histogram.total() as f64;
+        let explained = (1..=histogram.max_abundance())
+            .map(|x| x as f64 * model.predict(x))
+            .sum::<f64>();
+        let error = ((total - explained) / total).clamp(0.0, 1.0);
+        model.error_rate = 1.0 - (1.0 - error).powf(1.0 / k as f64);
+
+        Some(model)
+    }
+
+    /// Return number of distinct kmers with abundance x predicted by model
+    pub fn predict(&self, x: u32) -> f64 {
+        self.length
+            * shape(
+                &[self.coverage, self.heterozygosity, self.repeat, self.bias],
+                self.k,
+                x,
+            )
+    }
+}
+
+/// Probability of a kmer to have abundance x, params are coverage, heterozygosity, repeat and bias
+fn shape(params: &[f64; 4], k: u8, x: u32) -> f64 {
+    let [coverage, heterozygosity, repeat, bias] = *params;
+
+    let hom = (1.0 - heterozygosity).powi(k as i32);
+    let het = 1.0 - hom;
+
+    (1.0 - repeat)
+        * (2.0 * het * negative_binomial(x, coverage, bias)
+            + hom * negative_binomial(x, 2.0 * coverage, bias))
+        + repeat
+            * (2.0 * het * negative_binomial(x, 2.0 * coverage, bias)
+                + hom * negative_binomial(x, 4.0 * coverage, bias))
+}
+
+/// Return residual sum of square and best length for params
+fn objective(params: &[f64; 4], k: u8, observed: &[(u32, f64)]) -> (f64, f64) {
+    let [coverage, heterozygosity, repeat, bias] = *params;
+    if coverage < 0.5
+        || !(0.0..1.0).contains(&heterozygosity)
+        || !(0.0..=1.0).contains(&repeat)
+        || bias <= 0.0
+    {
+        return (f64::INFINITY, 0.0);
+    }
+
+    let shapes = observed
+        .iter()
+        .map(|(x, _)| shape(params, k, *x))
+        .collect::<Vec<f64>>();
+
+    // length is linear, best value is solve directly
+    let norm = shapes.iter().map(|f| f * f).sum::<f64>();
+    if norm == 0.0 {
+        return (f64::INFINITY, 0.0);
+    }
+    let length = shapes
+        .iter()
+        .zip(observed)
+        .map(|(f, (_, y))| f * y)
+        .sum::<f64>()
+        / norm;
+
+    let rss = shapes
+        .iter()
+        .zip(observed)
+        .map(|(f, (_, y))| (y - length * f).powi(2))
+        .sum::<f64>();
+
+    (rss, length)
+}
+
+/// Probability mass function of negative binomial with mean and variance mean(1 + bias)
+fn negative_binomial(x: u32, mean: f64, bias: f64) -> f64 {
+    let size = mean / bias;
+    let x = x as f64;
+
+    (ln_gamma(x + size) - ln_gamma(size) - ln_gamma(x + 1.0)
+        + size * (size / (size + mean)).ln()
+        + x * (mean / (size + mean)).ln())
+    .exp()
+}
+
+/// Logarithm of gamma function, Lanczos approximation
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        std::f64::consts::PI.ln() - (std::f64::consts::PI * x).sin().ln() - ln_gamma(1.0 - x)
+    } else {
+        let x = x - 1.0;
+        let t = x + 7.5;
+        let sum = COEFFICIENTS[1..]
+            .iter()
+            .enumerate()
+            .fold(COEFFICIENTS[0], |acc, (i, c)| {
+                acc + c / (x + i as f64 + 1.0)
+            });
+
+        0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+    }
+}
+
+/// Minimize function with Nelder-Mead simplex, return best point and its value
+fn nelder_mead<F>(function: F, start: [f64; 4], steps: [f64; 4]) -> ([f64; 4], f64)
+where
+    F: Fn(&[f64; 4]) -> f64,
+{
+    let mut simplex = vec![(start, function(&start))];
+    for (i, step) in steps.iter().enumerate() {
+        let mut point = start;
+        point[i] += step;
+        simplex.push((point, function(&point)));
+    }
+
+    for _ in 0..MAX_ITERATION {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let (best, worst) = (simplex[0].1, simplex[4].1);
+        if (worst - best).abs() <= 1e-10 * best.abs().max(1e-10) {
+            break;
+        }
+
+        let mut centroid = [0.0; 4];
+        for (point, _) in simplex[..4].iter() {
+            for (c, p) in centroid.iter_mut().zip(point) {
+                *c += p / 4.0;
+            }
+        }
+        let towards = |coefficient: f64| {
+            let mut point = centroid;
+            for (p, w) in point.iter_mut().zip(simplex[4].0.iter()) {
+                *p += coefficient * (w - *p);
+            }
+            point
+        };
+
+        let reflected = towards(-1.0);
+        let reflected_value = function(&reflected);
+
+        if reflected_value < simplex[0].1 {
+            let expanded = towards(-2.0);
+            let expanded_value = function(&expanded);
+            simplex[4] = if expanded_value < reflected_value {
+                (expanded, expanded_value)
+            } else {
+                (reflected, reflected_value)
+            };
+        } else if reflected_value < simplex[3].1 {
+            simplex[4] = (reflected, reflected_value);
+        } else {
+            let contracted = towards(0.5);
+            let contracted_value = function(&contracted);
+            if contracted_value < simplex[4].1 {
+                simplex[4] = (contracted, contracted_value);
+            } else {
+                let best = simplex[0].0;
+                for (point, value) in simplex[1..].iter_mut() {
+                    for (p, b) in point.iter_mut().zip(best.iter()) {
+                        *p = b + 0.5 * (*p - b);
+                    }
+                    *value = function(point);
+                }
+            }
+        }
+    }
+
+    simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    simplex[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synthetic(params: &[f64; 4], k: u8, length: f64, errors: f64) -> Histogram {
+        let mut histogram = Histogram::new();
+        histogram.counts = vec![0; 301];
+
+        for x in 1..=300 {
+            let error = errors * (x as f64).powi(-3);
+            histogram.counts[x as usize] = (length * shape(params, k, x) + error).round() as u64;
+        }
+
+        histogram
+    }
+
+    #[test]
+    fn ln_gamma_() {
+        assert!(ln_gamma(1.0).abs() < 1e-10);
+        assert!(ln_gamma(2.0).abs() < 1e-10);
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-10);
+        assert!((ln_gamma(0.5) - std::f64::consts::PI.sqrt().ln()).abs() < 1e-10);
+    }
+
+    #[test]
+    fn negative_binomial_() {
+        let sum = (0..1000)
+            .map(|x| negative_binomial(x, 30.0, 0.5))
+            .sum::<f64>();
+        assert!((sum - 1.0).abs() < 1e-6);
+
+        let mean = (0..1000)
+            .map(|x| x as f64 * negative_binomial(x, 30.0, 0.5))
+            .sum::<f64>();
+        assert!((mean - 30.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn diploid() {
+        let histogram = synthetic(&[15.0, 0.01, 0.02, 0.5], 21, 1e6, 2e6);
+        let model = Model::fit(&histogram, 21).unwrap();
+
+        assert!((model.coverage - 15.0).abs() < 0.5);
+        assert!((model.heterozygosity - 0.01).abs() < 0.001);
+        assert!((model.repeat - 0.02).abs() < 0.01);
+        assert!((model.genome_size / 1.02e6 - 1.0).abs() < 0.03);
+        assert!((0.003..0.007).contains(&model.error_rate));
+        assert!((model.predict(30) / histogram.get(30) as f64 - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn homozygous() {
+        let histogram = synthetic(&[20.0, 0.0, 0.0, 0.3], 21, 5e5, 1e6);
+        let model = Model::fit(&histogram, 21).unwrap();
+
+        assert!((model.coverage - 20.0).abs() < 0.5);
+        assert!(model.heterozygosity < 0.001);
+        assert!((model.genome_size / 5e5 - 1.0).abs() < 0.03);
+    }
+
+    #[test]
+    fn noisy_error_tail() {
+        let mut histogram = synthetic(&[15.0, 0.01, 0.02, 0.5], 21, 1e6, 2e6);
+
+        // error tail isn't monotone and all abundances have a small noise
+        for x in 1..=300 {
+            let noise = if x <= 8 {
+                if x % 2 == 0 {
+                    1.3
+                } else {
+                    0.8
+                }
+            } else {
+                1.0 + 0.03 * ((x * 7919 % 13) as f64 / 6.0 - 1.0)
+            };
+            histogram.counts[x] = (histogram.counts[x] as f64 * noise).round() as u64;
+        }
+        assert!(histogram.get(6) > histogram.get(5));
+
+        let model = Model::fit(&histogram, 21).unwrap();
+
+        assert!((model.coverage - 15.0).abs() < 0.5);
+        assert!((model.heterozygosity - 0.01).abs() < 0.002);
+        assert!((model.genome_size / 1.02e6 - 1.0).abs() < 0.05);
+        assert!(model.error_rate < 0.01);
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Model::fit(&Histogram::new(), 21), None);
+    }
+}
@@ -0,0 +1,38 @@
+//! Helpers shared by unit tests
+
+/* standard use */
+
+/* crates use */
+use biotest::Format as _;
+
+/* project use */
+
+/// Generate a random sequence of length len, same sequence is produce at each call
+pub(crate) fn random_seq(len: usize) -> Vec<u8> {
+    let mut rng = biotest::rand();
+    let generator = biotest::Sequence::builder()
+        .sequence_len(len)
+        .build()
+        .unwrap();
+    let mut seq = vec![];
+    generator.record(&mut seq, &mut rng).unwrap();
+
+    seq
+}
+
+/// Generate nb random reads of length len, same reads are produce at each call
+pub(crate) fn random_reads(nb: usize, len: usize) -> Vec<Vec<u8>> {
+    let mut rng = biotest::rand();
+    let generator = biotest::Sequence::builder()
+        .sequence_len(len)
+        .build()
+        .unwrap();
+
+    (0..nb)
+        .map(|_| {
+            let mut seq = vec![];
+            generator.record(&mut seq, &mut rng).unwrap();
+            seq
+        })
+        .collect()
+}
@@ -10,4 +10,9 @@
 pub mod basic;
 pub mod kmer;
 pub mod minimizer;
+pub mod packed;
+pub mod parallel;
+pub mod position;
 pub mod rle;
+pub mod stream;
+pub mod typed;
@@ -21,7 +21,9 @@ SOFTWARE.
  */
 
 /* local use */
+use crate::error;
 use crate::kmer;
+use crate::tokenizer::position::Position;
 
 /// An iterator that takes a DNA sequence and produces kmers, in the forward orientation and 2bit form.
 ///
@@ -36,6 +38,7 @@ use crate::kmer;
 ///     // ... do what you want ...
 /// }
 pub struct Tokenizer<'a> {
+    k: usize,
     kmer_mask: u64,
     seq: &'a [u8],
     pos: usize,
@@ -43,14 +46,33 @@ pub struct Tokenizer<'a> {
 }
 
 impl<'a> Tokenizer<'a> {
-    /// Create a new Tokenizer on seq DNA kmer size is equal to k
+    /// Create a new Tokenizer on seq DNA kmer size is equal to k, if seq is shorter than k no kmer is produce
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32, see [try_new](Tokenizer::try_new)
     pub fn new(seq: &'a [u8], k: u8) -> Self {
-        Tokenizer {
-            kmer_mask: (1 << (k * 2)) - 1,
+        Self::try_new(seq, k).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](Tokenizer::new) but return an error if k isn't in 1..=32
+    pub fn try_new(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, 32)?;
+
+        Ok(Tokenizer {
+            k: k as usize,
+            kmer_mask: u64::MAX >> (64 - k * 2),
             seq,
             pos: (k - 1) as usize,
-            kmer: kmer::seq2bit(unsafe { seq.get_unchecked(0..((k - 1) as usize)) }),
-        }
+            kmer: kmer::seq2bit(seq.get(0..((k - 1) as usize)).unwrap_or(seq)),
+        })
+    }
+
+    /// Same as [try_new](Tokenizer::try_new) but also return an error if seq contains a byte that isn't a nucleotide
+    pub fn new_checked(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        kmer::validate(seq)?;
+
+        Self::try_new(seq, k)
     }
 }
 
@@ -58,7 +80,7 @@ impl<'a> Iterator for Tokenizer<'a> {
     type Item = u64;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos == self.seq.len() {
+        if self.pos >= self.seq.len() {
             None
         } else {
             self.kmer = unsafe {
@@ -73,6 +95,90 @@ impl<'a> Iterator for Tokenizer<'a> {
     }
 }
 
+/// An iterator that takes a DNA sequence and produces kmers, in the forward orientation and 2bit form, kmers that contains a byte that isn't a nucleotide are skipped.
+///
+/// # Example
+///
+/// ```
+/// use cocktail::tokenizer::basic::TokenizerSkipN;
+///
+/// let tokenizer = TokenizerSkipN::new(b"GTACTGTGCCCGTGTTACTTNNNNNAAGCGTGAAAGGTGCGTGTTTCCGAGA", 5);
+///
+/// for kmer in tokenizer {
+///     // ... do what you want ...
+/// }
+pub struct TokenizerSkipN<'a> {
+    k: usize,
+    kmer_mask: u64,
+    seq: &'a [u8],
+    pos: usize,
+    valid: usize,
+    kmer: u64,
+}
+
+impl<'a> TokenizerSkipN<'a> {
+    /// Create a new TokenizerSkipN on seq DNA kmer size is equal to k
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32, see [try_new](TokenizerSkipN::try_new)
+    pub fn new(seq: &'a [u8], k: u8) -> Self {
+        Self::try_new(seq, k).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](TokenizerSkipN::new) but return an error if k isn't in 1..=32
+    pub fn try_new(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, 32)?;
+
+        Ok(TokenizerSkipN {
+            k: k as usize,
+            kmer_mask: u64::MAX >> (64 - k * 2),
+            seq,
+            pos: 0,
+            valid: 0,
+            kmer: 0,
+        })
+    }
+}
+
+impl<'a> Iterator for TokenizerSkipN<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.seq.len() {
+            let nuc = unsafe { *self.seq.get_unchecked(self.pos) };
+            self.pos += 1;
+
+            if !kmer::is_nuc(nuc) {
+                self.valid = 0;
+                self.kmer = 0;
+                continue;
+            }
+
+            self.kmer = ((self.kmer << 2) & self.kmer_mask) | kmer::nuc2bit(nuc);
+            self.valid += 1;
+
+            if self.valid >= self.k {
+                return Some(self.kmer);
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> Position for Tokenizer<'a> {
+    fn position(&self) -> usize {
+        self.pos - self.k
+    }
+}
+
+impl<'a> Position for TokenizerSkipN<'a> {
+    fn position(&self) -> usize {
+        self.pos - self.k
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -109,4 +215,57 @@ mod test {
                 .collect::<Vec<u64>>()
         );
     }
+
+    #[test]
+    fn seqlen_lower_k() {
+        assert!(Tokenizer::new(b"", 5).next().is_none());
+        assert!(Tokenizer::new(b"ACT", 5).next().is_none());
+        assert!(Tokenizer::new(b"ACTG", 5).next().is_none());
+    }
+
+    #[test]
+    fn k_32() {
+        let seq = b"ACTGACTGGATCAATGGACACGTACGATCGATCAG";
+
+        assert_eq!(
+            (0..4)
+                .map(|i| kmer::seq2bit(&seq[i..i + 32]))
+                .collect::<Vec<u64>>(),
+            Tokenizer::new(seq, 32).collect::<Vec<u64>>()
+        );
+        assert_eq!(
+            Tokenizer::try_new(seq, 33).err(),
+            Some(error::Error::KmerSize { k: 33, max: 32 })
+        );
+    }
+
+    #[test]
+    fn skip_n() {
+        assert_eq!(
+            vec![108, 433, 710, 795],
+            TokenizerSkipN::new(b"ACTGACTG", 5).collect::<Vec<u64>>()
+        );
+
+        assert_eq!(
+            vec![108, 108, 433],
+            TokenizerSkipN::new(b"ACTGANNACTGACNACTG", 5).collect::<Vec<u64>>()
+        );
+    }
+
+    #[test]
+    fn positions() {
+        assert_eq!(
+            vec![(0, 108), (1, 433), (2, 710), (3, 795)],
+            Tokenizer::new(b"ACTGACTG", 5)
+                .positions()
+                .collect::<Vec<(usize, u64)>>()
+        );
+
+        assert_eq!(
+            vec![(0, 108), (7, 108), (8, 433)],
+            TokenizerSkipN::new(b"ACTGANNACTGACNACTG", 5)
+                .positions()
+                .collect::<Vec<(usize, u64)>>()
+        );
+    }
 }
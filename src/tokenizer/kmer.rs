@@ -5,10 +5,15 @@
 /* crates use */
 
 /* project use */
+use crate::error;
 use crate::kmer;
+use crate::tokenizer::position::{Orientation, Position, Strand};
+use crate::tokenizer::typed::Typed;
 
 /// An iterator that takes a DNA sequence and produces kmers, in the forward orientation and 2bit form.
 ///
+/// Kmer are store in a `u64`, use `new_generic` to store them in any type that implement [Kmer](kmer::Kmer), k must be lower or equal to `K::MAX_K`.
+///
 /// # Example
 ///
 /// ```
@@ -19,35 +24,92 @@ use crate::kmer;
 /// for kmer in tokenizer {
 ///     // ... do what you want ...
 /// }
-pub struct Forward<'a> {
-    kmer_mask: u64,
+///
+/// let tokenizer = Forward::<u128>::new_generic(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA", 41);
+///
+/// for kmer in tokenizer {
+///     // ... do what you want ...
+/// }
+pub struct Forward<'a, K = u64> {
+    k: usize,
+    kmer_mask: K,
     seq: &'a [u8],
     pos: usize,
-    kmer: u64,
+    kmer: K,
 }
 
 impl<'a> Forward<'a> {
-    /// Create a new Forward on seq DNA kmer size is equal to k
+    /// Same as [new_generic](Forward::new_generic) with kmers store in a `u64`
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32, see [try_new](Forward::try_new)
     pub fn new(seq: &'a [u8], k: u8) -> Self {
-        Forward {
-            kmer_mask: (1 << (k * 2)) - 1,
+        Self::new_generic(seq, k)
+    }
+
+    /// Same as [try_new_generic](Forward::try_new_generic) with kmers store in a `u64`
+    pub fn try_new(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        Self::try_new_generic(seq, k)
+    }
+
+    /// Same as [new_checked_generic](Forward::new_checked_generic) with kmers store in a `u64`
+    pub fn new_checked(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        Self::new_checked_generic(seq, k)
+    }
+}
+
+impl<'a, K> Forward<'a, K>
+where
+    K: kmer::Kmer,
+{
+    /// Create a new Forward on seq DNA kmer size is equal to k, if seq is shorter than k no kmer is produce
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=`K::MAX_K`, see [try_new_generic](Forward::try_new_generic)
+    pub fn new_generic(seq: &'a [u8], k: u8) -> Self {
+        Self::try_new_generic(seq, k).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new_generic](Forward::new_generic) but return an error if k isn't in 1..=`K::MAX_K`
+    pub fn try_new_generic(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, K::MAX_K)?;
+
+        Ok(Forward {
+            k: k as usize,
+            kmer_mask: K::kmer_mask(k),
             seq,
             pos: (k - 1) as usize,
-            kmer: kmer::seq2bit(unsafe { seq.get_unchecked(0..((k - 1) as usize)) }),
-        }
+            kmer: K::seq2bit(seq.get(0..((k - 1) as usize)).unwrap_or(seq)),
+        })
+    }
+
+    /// Same as [try_new_generic](Forward::try_new_generic) but also return an error if seq contains a byte that isn't a nucleotide
+    pub fn new_checked_generic(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        kmer::validate(seq)?;
+
+        Self::try_new_generic(seq, k)
+    }
+
+    /// Create a new Forward on seq DNA, kmer size `N` is fixed at compile time and kmers are produce as [typed::Kmer](crate::typed::Kmer)
+    pub fn typed<const N: u8>(seq: &'a [u8]) -> Typed<Self, N> {
+        Typed::new(Self::new_generic(seq, N))
     }
 }
 
-impl<'a> Iterator for Forward<'a> {
-    type Item = u64;
+impl<'a, K> Iterator for Forward<'a, K>
+where
+    K: kmer::Kmer,
+{
+    type Item = K;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos == self.seq.len() {
+        if self.pos >= self.seq.len() {
             None
         } else {
             self.kmer = unsafe {
-                ((self.kmer << 2) & self.kmer_mask)
-                    | kmer::nuc2bit(*self.seq.get_unchecked(self.pos))
+                ((self.kmer << 2) & self.kmer_mask) | K::nuc2bit(*self.seq.get_unchecked(self.pos))
             };
 
             self.pos += 1;
@@ -59,6 +121,9 @@ impl<'a> Iterator for Forward<'a> {
 
 /// An iterator that takes a DNA sequence and produces kmers, in the canonical orientation and 2bit form.
 ///
+/// Kmer are store in a `u64`, use `new_generic` to store them in any type that implement [Kmer](kmer::Kmer), k must be lower or equal to `K::MAX_K`.
+/// If k is odd canonical kmer is selected with parity, see [canonical](kmer::canonical), if k is even with [canonical_min](kmer::canonical_min).
+///
 /// # Example
 ///
 /// ```
@@ -69,46 +134,114 @@ impl<'a> Iterator for Forward<'a> {
 /// for kmer in tokenizer {
 ///     // ... do what you want ...
 /// }
-pub struct Canonical<'a> {
+///
+/// let tokenizer = Canonical::<u128>::new_generic(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA", 41);
+///
+/// for kmer in tokenizer {
+///     // ... do what you want ...
+/// }
+pub struct Canonical<'a, K = u64> {
+    k: usize,
+    parity: bool,
+    forward: bool,
     move_bit: u8,
-    kmer_mask: u64,
+    kmer_mask: K,
     seq: &'a [u8],
     pos: usize,
-    kmers: [u64; 2],
+    kmers: [K; 2],
 }
 
 impl<'a> Canonical<'a> {
-    /// Create a new Canonical tokenizer on seq DNA, kmer size is equal to k
+    /// Same as [new_generic](Canonical::new_generic) with kmers store in a `u64`
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32, see [try_new](Canonical::try_new)
     pub fn new(seq: &'a [u8], k: u8) -> Self {
-        let forward = unsafe { kmer::seq2bit(seq.get_unchecked(0..((k - 1) as usize))) };
+        Self::new_generic(seq, k)
+    }
+
+    /// Same as [try_new_generic](Canonical::try_new_generic) with kmers store in a `u64`
+    pub fn try_new(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        Self::try_new_generic(seq, k)
+    }
 
-        Canonical {
+    /// Same as [new_checked_generic](Canonical::new_checked_generic) with kmers store in a `u64`
+    pub fn new_checked(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        Self::new_checked_generic(seq, k)
+    }
+}
+
+impl<'a, K> Canonical<'a, K>
+where
+    K: kmer::Kmer,
+{
+    /// Create a new Canonical tokenizer on seq DNA, kmer size is equal to k, if seq is shorter than k no kmer is produce
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=`K::MAX_K`, see [try_new_generic](Canonical::try_new_generic)
+    pub fn new_generic(seq: &'a [u8], k: u8) -> Self {
+        Self::try_new_generic(seq, k).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new_generic](Canonical::new_generic) but return an error if k isn't in 1..=`K::MAX_K`
+    pub fn try_new_generic(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, K::MAX_K)?;
+
+        let forward = K::seq2bit(seq.get(0..((k - 1) as usize)).unwrap_or(seq));
+
+        Ok(Canonical {
+            k: k as usize,
+            parity: k % 2 == 1,
+            forward: true,
             move_bit: (k - 1) * 2,
-            kmer_mask: (1 << (k * 2)) - 1,
+            kmer_mask: K::kmer_mask(k),
             seq,
             pos: (k - 1) as usize,
-            kmers: [forward, kmer::revcomp(forward, k)],
-        }
+            kmers: [forward, forward.revcomp(k)],
+        })
+    }
+
+    /// Same as [try_new_generic](Canonical::try_new_generic) but also return an error if seq contains a byte that isn't a nucleotide
+    pub fn new_checked_generic(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        kmer::validate(seq)?;
+
+        Self::try_new_generic(seq, k)
+    }
+
+    /// Create a new Canonical tokenizer on seq DNA, kmer size `N` is fixed at compile time and kmers are produce as [typed::Kmer](crate::typed::Kmer)
+    pub fn typed<const N: u8>(seq: &'a [u8]) -> Typed<Self, N> {
+        Typed::new(Self::new_generic(seq, N))
     }
 }
 
-impl<'a> Iterator for Canonical<'a> {
-    type Item = u64;
+impl<'a, K> Iterator for Canonical<'a, K>
+where
+    K: kmer::Kmer,
+{
+    type Item = K;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos == self.seq.len() {
+        if self.pos >= self.seq.len() {
             None
         } else {
             unsafe {
-                let nuc = kmer::nuc2bit(*self.seq.get_unchecked(self.pos));
+                let nuc = K::nuc2bit(*self.seq.get_unchecked(self.pos));
                 self.pos += 1;
 
                 *self.kmers.get_unchecked_mut(0) =
-                    ((self.kmers.get_unchecked(0) << 2) & self.kmer_mask) | nuc;
+                    ((*self.kmers.get_unchecked(0) << 2) & self.kmer_mask) | nuc;
                 *self.kmers.get_unchecked_mut(1) =
-                    (*self.kmers.get_unchecked(1) >> 2) ^ ((nuc ^ 0b10) << self.move_bit);
+                    (*self.kmers.get_unchecked(1) >> 2) ^ ((nuc ^ K::from(0b10)) << self.move_bit);
 
-                if kmer::parity_even(*self.kmers.get_unchecked(0)) {
+                self.forward = if self.parity {
+                    self.kmers.get_unchecked(0).parity_even()
+                } else {
+                    self.kmers.get_unchecked(0) <= self.kmers.get_unchecked(1)
+                };
+
+                if self.forward {
                     Some(*self.kmers.get_unchecked(0))
                 } else {
                     Some(*self.kmers.get_unchecked(1))
@@ -118,6 +251,281 @@ impl<'a> Iterator for Canonical<'a> {
     }
 }
 
+/// An iterator that takes a DNA sequence and produces kmers, in the forward orientation and 2bit form, kmers that contains a byte that isn't a nucleotide are skipped.
+///
+/// Kmer are store in a `u64`, use `new_generic` to store them in any type that implement [Kmer](kmer::Kmer), k must be lower or equal to `K::MAX_K`.
+///
+/// # Example
+///
+/// ```
+/// use cocktail::tokenizer::kmer::ForwardSkipN;
+///
+/// let tokenizer = ForwardSkipN::new(b"GTACTGTGCCCGTGTTACTTNNNNNAAGCGTGAAAGGTGCGTGTTTCCGAGA", 5);
+///
+/// for kmer in tokenizer {
+///     // ... do what you want ...
+/// }
+pub struct ForwardSkipN<'a, K = u64> {
+    k: usize,
+    kmer_mask: K,
+    seq: &'a [u8],
+    pos: usize,
+    valid: usize,
+    kmer: K,
+}
+
+impl<'a> ForwardSkipN<'a> {
+    /// Same as [new_generic](ForwardSkipN::new_generic) with kmers store in a `u64`
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32, see [try_new](ForwardSkipN::try_new)
+    pub fn new(seq: &'a [u8], k: u8) -> Self {
+        Self::new_generic(seq, k)
+    }
+
+    /// Same as [try_new_generic](ForwardSkipN::try_new_generic) with kmers store in a `u64`
+    pub fn try_new(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        Self::try_new_generic(seq, k)
+    }
+}
+
+impl<'a, K> ForwardSkipN<'a, K>
+where
+    K: kmer::Kmer,
+{
+    /// Create a new ForwardSkipN on seq DNA kmer size is equal to k
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=`K::MAX_K`, see [try_new_generic](ForwardSkipN::try_new_generic)
+    pub fn new_generic(seq: &'a [u8], k: u8) -> Self {
+        Self::try_new_generic(seq, k).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new_generic](ForwardSkipN::new_generic) but return an error if k isn't in 1..=`K::MAX_K`
+    pub fn try_new_generic(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, K::MAX_K)?;
+
+        Ok(ForwardSkipN {
+            k: k as usize,
+            kmer_mask: K::kmer_mask(k),
+            seq,
+            pos: 0,
+            valid: 0,
+            kmer: K::default(),
+        })
+    }
+}
+
+impl<'a, K> Iterator for ForwardSkipN<'a, K>
+where
+    K: kmer::Kmer,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.seq.len() {
+            let nuc = unsafe { *self.seq.get_unchecked(self.pos) };
+            self.pos += 1;
+
+            if !kmer::is_nuc(nuc) {
+                self.valid = 0;
+                self.kmer = K::default();
+                continue;
+            }
+
+            self.kmer = ((self.kmer << 2) & self.kmer_mask) | K::nuc2bit(nuc);
+            self.valid += 1;
+
+            if self.valid >= self.k {
+                return Some(self.kmer);
+            }
+        }
+
+        None
+    }
+}
+
+/// An iterator that takes a DNA sequence and produces kmers, in the canonical orientation and 2bit form, kmers that contains a byte that isn't a nucleotide are skipped.
+///
+/// Kmer are store in a `u64`, use `new_generic` to store them in any type that implement [Kmer](kmer::Kmer), k must be lower or equal to `K::MAX_K`.
+/// If k is odd canonical kmer is selected with parity, see [canonical](kmer::canonical), if k is even with [canonical_min](kmer::canonical_min).
+///
+/// # Example
+///
+/// ```
+/// use cocktail::tokenizer::kmer::CanonicalSkipN;
+///
+/// let tokenizer = CanonicalSkipN::new(b"GTACTGTGCCCGTGTTACTTNNNNNAAGCGTGAAAGGTGCGTGTTTCCGAGA", 5);
+///
+/// for kmer in tokenizer {
+///     // ... do what you want ...
+/// }
+pub struct CanonicalSkipN<'a, K = u64> {
+    k: usize,
+    parity: bool,
+    forward: bool,
+    move_bit: u8,
+    kmer_mask: K,
+    seq: &'a [u8],
+    pos: usize,
+    valid: usize,
+    kmers: [K; 2],
+}
+
+impl<'a> CanonicalSkipN<'a> {
+    /// Same as [new_generic](CanonicalSkipN::new_generic) with kmers store in a `u64`
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32, see [try_new](CanonicalSkipN::try_new)
+    pub fn new(seq: &'a [u8], k: u8) -> Self {
+        Self::new_generic(seq, k)
+    }
+
+    /// Same as [try_new_generic](CanonicalSkipN::try_new_generic) with kmers store in a `u64`
+    pub fn try_new(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        Self::try_new_generic(seq, k)
+    }
+}
+
+impl<'a, K> CanonicalSkipN<'a, K>
+where
+    K: kmer::Kmer,
+{
+    /// Create a new CanonicalSkipN tokenizer on seq DNA, kmer size is equal to k
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=`K::MAX_K`, see [try_new_generic](CanonicalSkipN::try_new_generic)
+    pub fn new_generic(seq: &'a [u8], k: u8) -> Self {
+        Self::try_new_generic(seq, k).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new_generic](CanonicalSkipN::new_generic) but return an error if k isn't in 1..=`K::MAX_K`
+    pub fn try_new_generic(seq: &'a [u8], k: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, K::MAX_K)?;
+
+        Ok(CanonicalSkipN {
+            k: k as usize,
+            parity: k % 2 == 1,
+            forward: true,
+            move_bit: (k - 1) * 2,
+            kmer_mask: K::kmer_mask(k),
+            seq,
+            pos: 0,
+            valid: 0,
+            kmers: [K::default(), K::default()],
+        })
+    }
+}
+
+impl<'a, K> Iterator for CanonicalSkipN<'a, K>
+where
+    K: kmer::Kmer,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.seq.len() {
+            let nuc = unsafe { *self.seq.get_unchecked(self.pos) };
+            self.pos += 1;
+
+            if !kmer::is_nuc(nuc) {
+                self.valid = 0;
+                self.kmers = [K::default(), K::default()];
+                continue;
+            }
+
+            let nuc = K::nuc2bit(nuc);
+            self.kmers[0] = ((self.kmers[0] << 2) & self.kmer_mask) | nuc;
+            self.kmers[1] = (self.kmers[1] >> 2) ^ ((nuc ^ K::from(0b10)) << self.move_bit);
+            self.valid += 1;
+
+            if self.valid < self.k {
+                continue;
+            }
+
+            self.forward = if self.parity {
+                self.kmers[0].parity_even()
+            } else {
+                self.kmers[0] <= self.kmers[1]
+            };
+
+            if self.forward {
+                return Some(self.kmers[0]);
+            } else {
+                return Some(self.kmers[1]);
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a, K> Position for Forward<'a, K>
+where
+    K: kmer::Kmer,
+{
+    fn position(&self) -> usize {
+        self.pos - self.k
+    }
+}
+
+impl<'a, K> Position for Canonical<'a, K>
+where
+    K: kmer::Kmer,
+{
+    fn position(&self) -> usize {
+        self.pos - self.k
+    }
+}
+
+impl<'a, K> Orientation for Canonical<'a, K>
+where
+    K: kmer::Kmer,
+{
+    fn strand(&self) -> Strand {
+        if self.forward {
+            Strand::Forward
+        } else {
+            Strand::Reverse
+        }
+    }
+}
+
+impl<'a, K> Position for ForwardSkipN<'a, K>
+where
+    K: kmer::Kmer,
+{
+    fn position(&self) -> usize {
+        self.pos - self.k
+    }
+}
+
+impl<'a, K> Position for CanonicalSkipN<'a, K>
+where
+    K: kmer::Kmer,
+{
+    fn position(&self) -> usize {
+        self.pos - self.k
+    }
+}
+
+impl<'a, K> Orientation for CanonicalSkipN<'a, K>
+where
+    K: kmer::Kmer,
+{
+    fn strand(&self) -> Strand {
+        if self.forward {
+            Strand::Forward
+        } else {
+            Strand::Reverse
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -126,20 +534,23 @@ mod tests {
     fn forward() {
         assert_eq!(
             vec![108, 433, 710, 795],
-            Forward::new(b"ACTGACTG", 5).collect::<Vec<u64>>()
+            Forward::<u64>::new(b"ACTGACTG", 5).collect::<Vec<u64>>()
         );
     }
 
     #[test]
     fn forward_equal_k() {
-        assert_eq!(vec![108], Forward::new(b"ACTGA", 5).collect::<Vec<u64>>());
+        assert_eq!(
+            vec![108],
+            Forward::<u64>::new(b"ACTGA", 5).collect::<Vec<u64>>()
+        );
     }
 
     #[test]
     fn forward_hash() {
         assert_eq!(
             vec![54, 457, 114, 397],
-            Forward::new(b"ACTGACTG", 5)
+            Forward::<u64>::new(b"ACTGACTG", 5)
                 .map(|x| crate::kmer::remove_first_bit(crate::kmer::canonical(x, 5)))
                 .collect::<Vec<u64>>()
         );
@@ -149,16 +560,284 @@ mod tests {
     fn canonical() {
         assert_eq!(
             vec![108, 915, 228, 795],
-            Canonical::new(b"ACTGACTG", 5).collect::<Vec<u64>>()
+            Canonical::<u64>::new(b"ACTGACTG", 5).collect::<Vec<u64>>()
         );
     }
 
     #[test]
     fn canonical_only_check() {
-        let token = Canonical::new(b"ACTGACTG", 5);
+        let token = Canonical::<u64>::new(b"ACTGACTG", 5);
 
         for cano in token {
             assert!(kmer::parity_even(cano));
         }
     }
+
+    #[test]
+    fn forward_u128() {
+        assert_eq!(
+            vec![108, 433, 710, 795],
+            Forward::<u128>::new_generic(b"ACTGACTG", 5).collect::<Vec<u128>>()
+        );
+
+        let seq = b"TAGGCGATTCATATAGCGTTACCATGGCATCGATCGATTACGGACTATTCAGGCTACGATTCAGC";
+        assert_eq!(
+            vec![
+                <u128 as kmer::Kmer>::seq2bit(&seq[..63]),
+                <u128 as kmer::Kmer>::seq2bit(&seq[1..64]),
+                <u128 as kmer::Kmer>::seq2bit(&seq[2..]),
+            ],
+            Forward::<u128>::new_generic(seq, 63).collect::<Vec<u128>>()
+        );
+    }
+
+    #[test]
+    fn canonical_even_k() {
+        let seq = b"ACTGACTGGATCAATGGACACGT";
+        let rev = crate::bytevec::revcomp(seq);
+
+        let fwd_canos = Canonical::<u64>::new(seq, 6).collect::<Vec<u64>>();
+        let mut rev_canos = Canonical::<u64>::new(&rev, 6).collect::<Vec<u64>>();
+        rev_canos.reverse();
+
+        assert_eq!(fwd_canos, rev_canos);
+        assert_eq!(
+            fwd_canos,
+            Forward::<u64>::new(seq, 6)
+                .map(|x| kmer::canonical_min(x, 6))
+                .collect::<Vec<u64>>()
+        );
+    }
+
+    #[test]
+    fn checked() {
+        assert_eq!(
+            vec![108, 433, 710, 795],
+            Forward::<u64>::new_checked(b"ACTGACTG", 5)
+                .unwrap()
+                .collect::<Vec<u64>>()
+        );
+        assert_eq!(
+            vec![108, 915, 228, 795],
+            Canonical::<u64>::new_checked(b"ACTGACTG", 5)
+                .unwrap()
+                .collect::<Vec<u64>>()
+        );
+
+        assert_eq!(
+            Forward::<u64>::new_checked(b"ACTGNCTG", 5).err(),
+            Some(error::Error::Nucleotide {
+                byte: b'N',
+                position: 4
+            })
+        );
+        assert_eq!(
+            Canonical::<u128>::new_checked_generic(b"ACTGACTx", 5).err(),
+            Some(error::Error::Nucleotide {
+                byte: b'x',
+                position: 7
+            })
+        );
+    }
+
+    fn valid_windows(seq: &[u8], k: usize) -> Vec<&[u8]> {
+        seq.windows(k)
+            .filter(|w| w.iter().all(|n| kmer::is_nuc(*n)))
+            .collect()
+    }
+
+    #[test]
+    fn forward_skip_n() {
+        let seq = b"NACTGNNACTGACNTTGACGGATN";
+
+        assert_eq!(
+            valid_windows(seq, 3)
+                .iter()
+                .map(|w| kmer::seq2bit(w))
+                .collect::<Vec<u64>>(),
+            ForwardSkipN::<u64>::new(seq, 3).collect::<Vec<u64>>()
+        );
+        assert_eq!(
+            Forward::<u64>::new(b"ACTGACTG", 5).collect::<Vec<u64>>(),
+            ForwardSkipN::<u64>::new(b"ACTGACTG", 5).collect::<Vec<u64>>()
+        );
+        assert!(ForwardSkipN::<u32>::new_generic(b"ACTGNACTGNACTGN", 5)
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn canonical_skip_n() {
+        let seq = b"NACTGNNACTGACNTTGACGGATNACGTACGTN";
+
+        for k in [3, 4] {
+            assert_eq!(
+                valid_windows(seq, k)
+                    .iter()
+                    .map(|w| if k % 2 == 1 {
+                        kmer::canonical(kmer::seq2bit(w), k as u8)
+                    } else {
+                        kmer::canonical_min(kmer::seq2bit(w), k as u8)
+                    })
+                    .collect::<Vec<u64>>(),
+                CanonicalSkipN::<u64>::new(seq, k as u8).collect::<Vec<u64>>()
+            );
+        }
+
+        assert_eq!(
+            Canonical::<u128>::new_generic(b"ACTGACTG", 5).collect::<Vec<u128>>(),
+            CanonicalSkipN::<u128>::new_generic(b"ACTGACTG", 5).collect::<Vec<u128>>()
+        );
+    }
+
+    #[test]
+    fn shorter_than_k() {
+        assert!(Forward::<u64>::new(b"", 5).next().is_none());
+        assert!(Forward::<u64>::new(b"ACT", 5).next().is_none());
+        assert!(Forward::<u64>::new(b"ACTG", 5).next().is_none());
+        assert!(Canonical::<u64>::new(b"", 5).next().is_none());
+        assert!(Canonical::<u64>::new(b"AC", 5).next().is_none());
+        assert!(Canonical::<u128>::new_generic(b"ACTG", 5).next().is_none());
+    }
+
+    #[test]
+    fn k_32() {
+        let seq = b"ACTGACTGGATCAATGGACACGTACGATCGATCAG";
+        let rev = crate::bytevec::revcomp(seq);
+
+        assert_eq!(
+            (0..4)
+                .map(|i| kmer::seq2bit(&seq[i..i + 32]))
+                .collect::<Vec<u64>>(),
+            Forward::<u64>::new(seq, 32).collect::<Vec<u64>>()
+        );
+
+        let fwd_canos = Canonical::<u64>::new(seq, 32).collect::<Vec<u64>>();
+        let mut rev_canos = Canonical::<u64>::new(&rev, 32).collect::<Vec<u64>>();
+        rev_canos.reverse();
+        assert_eq!(fwd_canos, rev_canos);
+    }
+
+    #[test]
+    fn invalid_k() {
+        assert_eq!(
+            Forward::<u64>::try_new(b"ACTG", 0).err(),
+            Some(error::Error::KmerSize { k: 0, max: 32 })
+        );
+        assert_eq!(
+            Canonical::<u64>::try_new(b"ACTG", 33).err(),
+            Some(error::Error::KmerSize { k: 33, max: 32 })
+        );
+        assert_eq!(
+            ForwardSkipN::<u16>::try_new_generic(b"ACTG", 9).err(),
+            Some(error::Error::KmerSize { k: 9, max: 8 })
+        );
+        assert!(CanonicalSkipN::<u128>::try_new_generic(b"ACTG", 0).is_err());
+        assert!(Canonical::<u128>::try_new_generic(b"ACTG", 64).is_ok());
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_k_0() {
+        Forward::<u64>::new(b"ACTG", 0);
+    }
+
+    #[test]
+    fn positions() {
+        let seq = b"NACTGNNACTGACNTTGACGGATN";
+
+        assert_eq!(
+            vec![(0, 108), (1, 433), (2, 710), (3, 795)],
+            Forward::<u64>::new(b"ACTGACTG", 5)
+                .positions()
+                .collect::<Vec<(usize, u64)>>()
+        );
+
+        for (pos, kmer) in ForwardSkipN::<u64>::new(seq, 3).positions() {
+            assert_eq!(kmer, kmer::seq2bit(&seq[pos..pos + 3]));
+        }
+        assert_eq!(
+            vec![1, 2, 7, 8, 9, 10, 14, 15, 16, 17, 18, 19, 20],
+            ForwardSkipN::<u64>::new(seq, 3)
+                .positions()
+                .map(|(pos, _)| pos)
+                .collect::<Vec<usize>>()
+        );
+    }
+
+    #[test]
+    fn stranded() {
+        assert_eq!(
+            vec![
+                (0, 108, Strand::Forward),
+                (1, 915, Strand::Reverse),
+                (2, 228, Strand::Reverse),
+                (3, 795, Strand::Forward)
+            ],
+            Canonical::<u64>::new(b"ACTGACTG", 5)
+                .stranded()
+                .collect::<Vec<(usize, u64, Strand)>>()
+        );
+
+        let seq = b"NACTGNNACTGACNTTGACGGATNACGTACGTN";
+        for k in [3, 4] {
+            for (pos, kmer, strand) in CanonicalSkipN::<u64>::new(seq, k).stranded() {
+                let forward = kmer::seq2bit(&seq[pos..pos + k as usize]);
+                match strand {
+                    Strand::Forward => assert_eq!(kmer, forward),
+                    Strand::Reverse => assert_eq!(kmer, kmer::revcomp(forward, k)),
+                }
+            }
+        }
+
+        // ACGT is is own reverse complement
+        assert_eq!(
+            vec![(0, kmer::seq2bit(b"ACGT"), Strand::Forward)],
+            Canonical::<u64>::new(b"ACGT", 4)
+                .stranded()
+                .collect::<Vec<(usize, u64, Strand)>>()
+        );
+    }
+
+    #[test]
+    fn small_type() {
+        assert_eq!(
+            vec![108, 433, 710, 795],
+            Forward::<u16>::new_generic(b"ACTGACTG", 5).collect::<Vec<u16>>()
+        );
+
+        assert_eq!(
+            vec![108, 915, 228, 795],
+            Canonical::<u32>::new_generic(b"ACTGACTG", 5).collect::<Vec<u32>>()
+        );
+
+        assert_eq!(
+            Canonical::<u64>::new(b"ACTGACTGGATCAATGGAC", 16)
+                .map(|x| x as u32)
+                .collect::<Vec<u32>>(),
+            Canonical::<u32>::new_generic(b"ACTGACTGGATCAATGGAC", 16).collect::<Vec<u32>>()
+        );
+    }
+
+    #[test]
+    fn canonical_u128() {
+        assert_eq!(
+            vec![108, 915, 228, 795],
+            Canonical::<u128>::new_generic(b"ACTGACTG", 5).collect::<Vec<u128>>()
+        );
+
+        let seq = b"TAGGCGATTCATATAGCGTTACCATGGCATCGATCGATTACGGACTATTCAGGCTACGATTCAGC";
+        assert_eq!(
+            Forward::<u128>::new_generic(seq, 63)
+                .map(|x| kmer::Kmer::canonical(x, 63))
+                .collect::<Vec<u128>>(),
+            Canonical::<u128>::new_generic(seq, 63).collect::<Vec<u128>>()
+        );
+        assert_eq!(
+            Forward::<u128>::new_generic(seq, 64)
+                .map(|x| kmer::Kmer::canonical_min(x, 64))
+                .collect::<Vec<u128>>(),
+            Canonical::<u128>::new_generic(seq, 64).collect::<Vec<u128>>()
+        );
+    }
 }
@@ -13,4 +13,6 @@ pub mod method;
 
 /* reexport */
 pub use canonical::Canonical;
+pub use canonical::CanonicalSkipN;
 pub use forward::Forward;
+pub use forward::ForwardSkipN;
@@ -6,78 +6,115 @@
 
 /* local use */
 use crate::bytevec;
+use crate::error;
 use crate::kmer;
 use crate::tokenizer::minimizer::method;
+use crate::tokenizer::position::{Orientation, Position, Strand};
 
 /// An iterator that takes a DNA sequence and produces kmers (in the canonical direction and 2bit form) and the associated minimizer.
 ///
+/// If k is odd canonical kmer is selected with parity, see [canonical](kmer::canonical), if k is even with [canonical_min](kmer::canonical_min).
+///
 /// # Example
 ///
 /// ```
 /// use cocktail::tokenizer::minimizer::Canonical;
 /// use cocktail::tokenizer::minimizer::method;
 ///
-/// let tokenizer = Canonical::<method::Random, u64>::new(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA", 8, 7);
+/// let tokenizer = Canonical::<method::Random>::new(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA", 8, 7);
 ///
 /// for kmer in tokenizer {
 ///     // ... do what you want ...
 /// }
-pub struct Canonical<'a, M, K>
+pub struct Canonical<'a, M, K = u64>
 where
     M: method::Method<K>,
 {
+    k: u8,
+    parity: bool,
+    forward: bool,
     move_bit: u8,
-    kmer_mask: u64,
+    kmer_mask: K,
     seq: &'a [u8],
     pos: usize,
     kmers: [K; 2],
     minimizer: M,
 }
 
-impl<'a, M> Canonical<'a, M, u64>
+impl<'a, M, K> Canonical<'a, M, K>
 where
-    M: method::Method<u64>,
+    M: method::Method<K>,
+    K: kmer::Kmer,
 {
-    /// Create a new Canonical tokenizer on seq DNA, kmer size is equal to k
+    /// Create a new Canonical tokenizer on seq DNA, kmer size is equal to k, if seq is shorter than k no kmer is produce
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=`K::MAX_K` or m isn't in 1..=min(k, 32), see [try_new](Canonical::try_new)
     pub fn new(seq: &'a [u8], k: u8, m: u8) -> Self {
-        let forward = unsafe { kmer::seq2bit(seq.get_unchecked(0..((k - 1) as usize))) };
+        Self::try_new(seq, k, m).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](Canonical::new) but return an error if k isn't in 1..=`K::MAX_K` or m isn't in 1..=min(k, 32)
+    pub fn try_new(seq: &'a [u8], k: u8, m: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, K::MAX_K)?;
+        kmer::check_minimizer_size(k.min(32), m)?;
+
+        let forward = K::seq2bit(seq.get(0..((k - 1) as usize)).unwrap_or(seq));
 
         let mut minimizer = M::default();
         minimizer.init(k, m, forward);
 
-        Canonical {
+        Ok(Canonical {
+            k,
+            parity: k % 2 == 1,
+            forward: true,
             move_bit: (k - 1) * 2,
-            kmer_mask: (1 << (k * 2)) - 1,
+            kmer_mask: K::kmer_mask(k),
             seq,
             pos: (k - 1) as usize,
-            kmers: [forward, kmer::revcomp(forward, k)],
+            kmers: [forward, forward.revcomp(k)],
             minimizer,
-        }
+        })
+    }
+
+    /// Same as [try_new](Canonical::try_new) but also return an error if seq contains a byte that isn't a nucleotide
+    pub fn new_checked(seq: &'a [u8], k: u8, m: u8) -> Result<Self, error::Error> {
+        kmer::validate(seq)?;
+
+        Self::try_new(seq, k, m)
     }
 }
 
-impl<'a, M> Iterator for Canonical<'a, M, u64>
+impl<'a, M, K> Iterator for Canonical<'a, M, K>
 where
-    M: method::Method<u64>,
+    M: method::Method<K>,
+    K: kmer::Kmer,
 {
-    type Item = (u64, u64);
+    type Item = (K, u64);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos == self.seq.len() {
+        if self.pos >= self.seq.len() {
             None
         } else {
             unsafe {
-                let nuc = kmer::nuc2bit(*self.seq.get_unchecked(self.pos));
+                let nuc = K::nuc2bit(*self.seq.get_unchecked(self.pos));
                 self.pos += 1;
 
                 *self.kmers.get_unchecked_mut(0) =
-                    ((self.kmers.get_unchecked(0) << 2) & self.kmer_mask) | nuc;
+                    ((*self.kmers.get_unchecked(0) << 2) & self.kmer_mask) | nuc;
                 *self.kmers.get_unchecked_mut(1) =
-                    (*self.kmers.get_unchecked(1) >> 2) ^ ((nuc ^ 0b10) << self.move_bit);
+                    (*self.kmers.get_unchecked(1) >> 2) ^ ((nuc ^ K::from(0b10)) << self.move_bit);
 
                 self.minimizer.add_kmer(*self.kmers.get_unchecked(0));
 
-                if kmer::parity_even(*self.kmers.get_unchecked(0)) {
+                self.forward = if self.parity {
+                    self.kmers.get_unchecked(0).parity_even()
+                } else {
+                    self.kmers.get_unchecked(0) <= self.kmers.get_unchecked(1)
+                };
+
+                if self.forward {
                     Some((*self.kmers.get_unchecked(0), self.minimizer.get_mini().0))
                 } else {
                     Some((*self.kmers.get_unchecked(1), self.minimizer.get_mini().0))
@@ -91,9 +128,22 @@ impl<'a, M> Canonical<'a, M, Vec<u8>>
 where
     M: method::Method<Vec<u8>>,
 {
-    /// Create a new Canonical tokenizer on seq DNA, kmer size is equal to k
+    /// Create a new Canonical tokenizer on seq DNA, kmer size is equal to k, if seq is shorter than k no kmer is produce
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=255 or m isn't in 1..=min(k, 32), see [try_new](Canonical::try_new)
     pub fn new(seq: &'a [u8], k: u8, m: u8) -> Self {
-        let mut forward = unsafe { seq.get_unchecked(0..((k - 1) as usize)).to_vec() };
+        Self::try_new(seq, k, m).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](Canonical::new) but return an error if k isn't in 1..=255 or m isn't in 1..=min(k, 32)
+    pub fn try_new(seq: &'a [u8], k: u8, m: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, u8::MAX)?;
+        kmer::check_minimizer_size(k.min(32), m)?;
+
+        let mut forward = seq.get(0..((k - 1) as usize)).unwrap_or(seq).to_vec();
+        forward.resize(k as usize - 1, b'A');
         forward.push(b'n');
 
         let mut minimizer = M::default();
@@ -102,14 +152,24 @@ where
         forward.rotate_right(1);
         let reverse = bytevec::revcomp(&forward);
 
-        Canonical {
+        Ok(Canonical {
+            k,
+            parity: k % 2 == 1,
+            forward: true,
             move_bit: (k - 1) * 2,
-            kmer_mask: (1 << (k * 2)) - 1,
+            kmer_mask: Vec::new(),
             seq,
             pos: (k - 1) as usize,
             kmers: [forward, reverse],
             minimizer,
-        }
+        })
+    }
+
+    /// Same as [try_new](Canonical::try_new) but also return an error if seq contains a byte that isn't a nucleotide
+    pub fn new_checked(seq: &'a [u8], k: u8, m: u8) -> Result<Self, error::Error> {
+        kmer::validate(seq)?;
+
+        Self::try_new(seq, k, m)
     }
 }
 
@@ -120,7 +180,7 @@ where
     type Item = (Vec<u8>, u64);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos == self.seq.len() {
+        if self.pos >= self.seq.len() {
             None
         } else {
             unsafe {
@@ -139,7 +199,9 @@ where
                 self.minimizer
                     .add_kmer(self.kmers.get_unchecked(0).to_vec());
 
-                if self.kmers.get_unchecked(0) < self.kmers.get_unchecked(1) {
+                self.forward = self.kmers.get_unchecked(0) <= self.kmers.get_unchecked(1);
+
+                if self.forward {
                     Some((
                         self.kmers.get_unchecked(0).to_vec(),
                         self.minimizer.get_mini().0,
@@ -155,6 +217,172 @@ where
     }
 }
 
+/// An iterator that takes a DNA sequence and produces kmers (in the canonical direction and 2bit form) and the associated minimizer, kmers that contains a byte that isn't a nucleotide are skipped.
+///
+/// Minimizer method is reset after each invalid byte.
+/// If k is odd canonical kmer is selected with parity, see [canonical](kmer::canonical), if k is even with [canonical_min](kmer::canonical_min).
+///
+/// # Example
+///
+/// ```
+/// use cocktail::tokenizer::minimizer::CanonicalSkipN;
+/// use cocktail::tokenizer::minimizer::method;
+///
+/// let tokenizer = CanonicalSkipN::<method::Random>::new(b"GTACTGTGCCCGTGTTACTTNNNNNAAGCGTGAAAGGTGCGTGTTTCCGAGA", 8, 7);
+///
+/// for (kmer, minimizer) in tokenizer {
+///     // ... do what you want ...
+/// }
+pub struct CanonicalSkipN<'a, M, K = u64>
+where
+    M: method::Method<K>,
+{
+    k: u8,
+    m: u8,
+    parity: bool,
+    forward: bool,
+    move_bit: u8,
+    kmer_mask: K,
+    seq: &'a [u8],
+    pos: usize,
+    valid: usize,
+    kmers: [K; 2],
+    minimizer: M,
+}
+
+impl<'a, M, K> CanonicalSkipN<'a, M, K>
+where
+    M: method::Method<K>,
+    K: kmer::Kmer,
+{
+    /// Create a new CanonicalSkipN tokenizer on seq DNA, kmer size is equal to k, minimizer size is equal to m
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=`K::MAX_K` or m isn't in 1..=min(k, 32), see [try_new](CanonicalSkipN::try_new)
+    pub fn new(seq: &'a [u8], k: u8, m: u8) -> Self {
+        Self::try_new(seq, k, m).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](CanonicalSkipN::new) but return an error if k isn't in 1..=`K::MAX_K` or m isn't in 1..=min(k, 32)
+    pub fn try_new(seq: &'a [u8], k: u8, m: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, K::MAX_K)?;
+        kmer::check_minimizer_size(k.min(32), m)?;
+
+        Ok(CanonicalSkipN {
+            k,
+            m,
+            parity: k % 2 == 1,
+            forward: true,
+            move_bit: (k - 1) * 2,
+            kmer_mask: K::kmer_mask(k),
+            seq,
+            pos: 0,
+            valid: 0,
+            kmers: [K::default(), K::default()],
+            minimizer: M::default(),
+        })
+    }
+}
+
+impl<'a, M, K> Iterator for CanonicalSkipN<'a, M, K>
+where
+    M: method::Method<K>,
+    K: kmer::Kmer,
+{
+    type Item = (K, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.seq.len() {
+            let nuc = self.seq[self.pos];
+            self.pos += 1;
+
+            if !kmer::is_nuc(nuc) {
+                self.valid = 0;
+                self.kmers = [K::default(), K::default()];
+                continue;
+            }
+
+            let nuc = K::nuc2bit(nuc);
+            self.kmers[0] = ((self.kmers[0] << 2) & self.kmer_mask) | nuc;
+            self.kmers[1] = (self.kmers[1] >> 2) ^ ((nuc ^ K::from(0b10)) << self.move_bit);
+            self.valid += 1;
+
+            if self.valid < self.k as usize {
+                continue;
+            } else if self.valid == self.k as usize {
+                self.minimizer.init(self.k, self.m, self.kmers[0] >> 2);
+            }
+
+            self.minimizer.add_kmer(self.kmers[0]);
+
+            self.forward = if self.parity {
+                self.kmers[0].parity_even()
+            } else {
+                self.kmers[0] <= self.kmers[1]
+            };
+
+            let cano = if self.forward {
+                self.kmers[0]
+            } else {
+                self.kmers[1]
+            };
+
+            return Some((cano, self.minimizer.get_mini().0));
+        }
+
+        None
+    }
+}
+
+impl<'a, M, K> Position for Canonical<'a, M, K>
+where
+    Canonical<'a, M, K>: Iterator,
+    M: method::Method<K>,
+{
+    fn position(&self) -> usize {
+        self.pos - self.k as usize
+    }
+}
+
+impl<'a, M, K> Orientation for Canonical<'a, M, K>
+where
+    Canonical<'a, M, K>: Iterator,
+    M: method::Method<K>,
+{
+    fn strand(&self) -> Strand {
+        if self.forward {
+            Strand::Forward
+        } else {
+            Strand::Reverse
+        }
+    }
+}
+
+impl<'a, M, K> Position for CanonicalSkipN<'a, M, K>
+where
+    CanonicalSkipN<'a, M, K>: Iterator,
+    M: method::Method<K>,
+{
+    fn position(&self) -> usize {
+        self.pos - self.k as usize
+    }
+}
+
+impl<'a, M, K> Orientation for CanonicalSkipN<'a, M, K>
+where
+    CanonicalSkipN<'a, M, K>: Iterator,
+    M: method::Method<K>,
+{
+    fn strand(&self) -> Strand {
+        if self.forward {
+            Strand::Forward
+        } else {
+            Strand::Reverse
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     /* std use */
@@ -279,6 +507,128 @@ mod tests {
         );
     }
 
+    #[test]
+    fn u128() {
+        let mut rng = biotest::rand();
+        let generator = biotest::Sequence::builder()
+            .sequence_len(80)
+            .build()
+            .unwrap();
+        let mut seq = vec![];
+        generator.record(&mut seq, &mut rng).unwrap();
+
+        let small: Vec<(u128, u64)> = Canonical::<method::Random, u64>::new(&seq, 11, 7)
+            .map(|(kmer, mini)| (kmer as u128, mini))
+            .collect();
+        let large: Vec<(u128, u64)> = Canonical::<method::Random, u128>::new(&seq, 11, 7).collect();
+
+        assert_eq!(small, large);
+
+        let rev = bytevec::revcomp(&seq);
+        let fwd_token = Canonical::<method::Random, u128>::new(&seq, 51, 21);
+        let rev_token = Canonical::<method::Random, u128>::new(&rev, 51, 21);
+
+        let fwd: Vec<(u128, u64)> = fwd_token.collect();
+        let mut rev: Vec<(u128, u64)> = rev_token.collect();
+        rev.reverse();
+
+        assert_eq!(fwd, rev);
+        for (kmer, _) in fwd {
+            assert!(kmer::Kmer::parity_even(kmer));
+        }
+    }
+
+    #[test]
+    fn even_k_m() {
+        let mut rng = biotest::rand();
+        let generator = biotest::Sequence::builder()
+            .sequence_len(60)
+            .build()
+            .unwrap();
+        let mut seq = vec![];
+        generator.record(&mut seq, &mut rng).unwrap();
+        let rev = bytevec::revcomp(&seq);
+
+        let fwd: Vec<(u64, u64)> = Canonical::<method::Random, u64>::new(&seq, 12, 8).collect();
+        let mut rev: Vec<(u64, u64)> = Canonical::<method::Random, u64>::new(&rev, 12, 8).collect();
+        rev.reverse();
+
+        assert_eq!(fwd, rev);
+        for (i, (cano, _)) in fwd.iter().enumerate() {
+            assert_eq!(*cano, kmer::hash_min(&seq[i..i + 12], 12));
+        }
+    }
+
+    #[test]
+    fn odd_k_even_m() {
+        let mut rng = biotest::rand();
+        let generator = biotest::Sequence::builder()
+            .sequence_len(80)
+            .build()
+            .unwrap();
+        let mut seq = vec![];
+        generator.record(&mut seq, &mut rng).unwrap();
+        let rev = bytevec::revcomp(&seq);
+
+        let fwd: Vec<(u64, u64)> = Canonical::<method::Random, u64>::new(&seq, 15, 8).collect();
+        let mut rev: Vec<(u64, u64)> = Canonical::<method::Random, u64>::new(&rev, 15, 8).collect();
+        rev.reverse();
+
+        assert_eq!(fwd, rev);
+        for (i, (_, mini)) in fwd.iter().enumerate() {
+            assert_eq!(*mini, kmer::canonical_min(*mini, 8));
+            assert!(seq[i..i + 15]
+                .windows(8)
+                .any(|w| kmer::canonical_min(kmer::seq2bit(w), 8) == *mini));
+        }
+    }
+
+    #[test]
+    fn skip_n() {
+        let mut rng = biotest::rand();
+        let generator = biotest::Sequence::builder()
+            .sequence_len(50)
+            .build()
+            .unwrap();
+        let mut seq = vec![];
+        generator.record(&mut seq, &mut rng).unwrap();
+
+        let mut with_n = seq.clone();
+        with_n[20] = b'N';
+        with_n[25] = b'n';
+
+        for (k, m) in [(11, 7), (12, 8)] {
+            assert_eq!(
+                Canonical::<method::Random, u64>::new(&seq, k, m).collect::<Vec<(u64, u64)>>(),
+                CanonicalSkipN::<method::Random, u64>::new(&seq, k, m).collect::<Vec<(u64, u64)>>()
+            );
+
+            let expected = Canonical::<method::Random, u64>::new(&seq[..20], k, m)
+                .chain(Canonical::<method::Random, u64>::new(&seq[26..], k, m))
+                .collect::<Vec<(u64, u64)>>();
+
+            assert_eq!(
+                expected,
+                CanonicalSkipN::<method::Random, u64>::new(&with_n, k, m)
+                    .collect::<Vec<(u64, u64)>>()
+            );
+        }
+    }
+
+    #[test]
+    fn shorter_than_k() {
+        assert!(Canonical::<method::Random, u64>::new(b"", 5, 3)
+            .next()
+            .is_none());
+        assert!(Canonical::<method::Random, u64>::new(b"ACTG", 5, 3)
+            .next()
+            .is_none());
+        assert!(Canonical::<method::Random, Vec<u8>>::new(b"AC", 5, 3)
+            .next()
+            .is_none());
+        assert!(Canonical::<method::Random, u64>::try_new(b"ACTG", 3, 5).is_err());
+    }
+
     #[test]
     fn bytevec() {
         let mut rng = biotest::rand();
@@ -393,4 +743,49 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn stranded() {
+        let seq = b"ACTGACTGGANCAATGGACACGT";
+
+        for k in [5, 6] {
+            for (pos, (kmer, _), strand) in
+                CanonicalSkipN::<method::Random, u64>::new(seq, k, 3).stranded()
+            {
+                let forward = kmer::seq2bit(&seq[pos..pos + k as usize]);
+                match strand {
+                    Strand::Forward => assert_eq!(kmer, forward),
+                    Strand::Reverse => assert_eq!(kmer, kmer::revcomp(forward, k)),
+                }
+            }
+
+            for (pos, (kmer, _), strand) in
+                Canonical::<method::Random, u64>::new(&seq[..10], k, 3).stranded()
+            {
+                let forward = kmer::seq2bit(&seq[pos..pos + k as usize]);
+                match strand {
+                    Strand::Forward => assert_eq!(kmer, forward),
+                    Strand::Reverse => assert_eq!(kmer, kmer::revcomp(forward, k)),
+                }
+            }
+
+            for (pos, (kmer, _), strand) in
+                Canonical::<method::Random, Vec<u8>>::new(&seq[..10], k, 3).stranded()
+            {
+                let forward = seq[pos..pos + k as usize].to_vec();
+                match strand {
+                    Strand::Forward => assert_eq!(kmer, forward),
+                    Strand::Reverse => assert_eq!(kmer, bytevec::revcomp(&forward)),
+                }
+            }
+        }
+
+        assert_eq!(
+            vec![0, 1, 2, 3, 4, 5, 11, 12, 13, 14, 15, 16, 17, 18],
+            CanonicalSkipN::<method::Random, u64>::new(seq, 5, 3)
+                .positions()
+                .map(|(pos, _)| pos)
+                .collect::<Vec<usize>>()
+        );
+    }
 }
@@ -5,8 +5,10 @@
 /* crates use */
 
 /* local use */
+use crate::error;
 use crate::kmer;
 use crate::tokenizer::minimizer::method;
+use crate::tokenizer::position::Position;
 
 /// An iterator that takes a DNA sequence and produces kmers (in the forward direction and 2bit form) and the associated minimizer.
 ///
@@ -16,54 +18,77 @@ use crate::tokenizer::minimizer::method;
 /// use cocktail::tokenizer::minimizer::Forward;
 /// use cocktail::tokenizer::minimizer::method;
 ///
-/// let tokenizer = Forward::<method::Random, u64>::new(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA", 5, 3);
+/// let tokenizer = Forward::<method::Random>::new(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA", 5, 3);
 ///
 /// for (kmer, minimizer) in tokenizer {
 ///     // ... do what you want ...
 /// }
-pub struct Forward<'a, M, K>
+pub struct Forward<'a, M, K = u64>
 where
     M: method::Method<K>,
 {
-    kmer_mask: u64,
+    k: u8,
+    kmer_mask: K,
     seq: &'a [u8],
     pos: usize,
     kmer: K,
     minimizer: M,
 }
 
-impl<'a, M> Forward<'a, M, u64>
+impl<'a, M, K> Forward<'a, M, K>
 where
-    M: method::Method<u64>,
+    M: method::Method<K>,
+    K: kmer::Kmer,
 {
-    /// Create a new Forward on seq DNA kmer size is equal to k, minimizer size is equal to m
+    /// Create a new Forward on seq DNA kmer size is equal to k, minimizer size is equal to m, if seq is shorter than k no kmer is produce
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=`K::MAX_K` or m isn't in 1..=min(k, 32), see [try_new](Forward::try_new)
     pub fn new(seq: &'a [u8], k: u8, m: u8) -> Self {
-        let kmer = unsafe { kmer::seq2bit(seq.get_unchecked(0..((k - 1) as usize))) };
+        Self::try_new(seq, k, m).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](Forward::new) but return an error if k isn't in 1..=`K::MAX_K` or m isn't in 1..=min(k, 32)
+    pub fn try_new(seq: &'a [u8], k: u8, m: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, K::MAX_K)?;
+        kmer::check_minimizer_size(k.min(32), m)?;
+
+        let kmer = K::seq2bit(seq.get(0..((k - 1) as usize)).unwrap_or(seq));
 
         let mut minimizer = M::default();
         minimizer.init(k, m, kmer);
 
-        Self {
-            kmer_mask: (1 << (k * 2)) - 1,
+        Ok(Self {
+            k,
+            kmer_mask: K::kmer_mask(k),
             seq,
             pos: (k - 1) as usize,
             kmer,
             minimizer,
-        }
+        })
+    }
+
+    /// Same as [try_new](Forward::try_new) but also return an error if seq contains a byte that isn't a nucleotide
+    pub fn new_checked(seq: &'a [u8], k: u8, m: u8) -> Result<Self, error::Error> {
+        kmer::validate(seq)?;
+
+        Self::try_new(seq, k, m)
     }
 }
 
-impl<'a, M> Iterator for Forward<'a, M, u64>
+impl<'a, M, K> Iterator for Forward<'a, M, K>
 where
-    M: method::Method<u64>,
+    M: method::Method<K>,
+    K: kmer::Kmer,
 {
-    type Item = (u64, u64);
+    type Item = (K, u64);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos == self.seq.len() {
+        if self.pos >= self.seq.len() {
             None
         } else {
-            self.kmer = ((self.kmer << 2) & self.kmer_mask) | kmer::nuc2bit(self.seq[self.pos]);
+            self.kmer = ((self.kmer << 2) & self.kmer_mask) | K::nuc2bit(self.seq[self.pos]);
 
             self.minimizer.add_kmer(self.kmer);
 
@@ -78,9 +103,22 @@ impl<'a, M> Forward<'a, M, Vec<u8>>
 where
     M: method::Method<Vec<u8>>,
 {
-    /// Create a new Forward on seq DNA kmer size is equal to k, minimizer size is equal to m
+    /// Create a new Forward on seq DNA kmer size is equal to k, minimizer size is equal to m, if seq is shorter than k no kmer is produce
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=255 or m isn't in 1..=min(k, 32), see [try_new](Forward::try_new)
     pub fn new(seq: &'a [u8], k: u8, m: u8) -> Self {
-        let mut kmer = unsafe { seq.get_unchecked(0..(k as usize - 1)).to_vec() };
+        Self::try_new(seq, k, m).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](Forward::new) but return an error if k isn't in 1..=255 or m isn't in 1..=min(k, 32)
+    pub fn try_new(seq: &'a [u8], k: u8, m: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, u8::MAX)?;
+        kmer::check_minimizer_size(k.min(32), m)?;
+
+        let mut kmer = seq.get(0..(k as usize - 1)).unwrap_or(seq).to_vec();
+        kmer.resize(k as usize - 1, b'A');
         kmer.push(b'n');
 
         let mut minimizer = M::default();
@@ -88,13 +126,21 @@ where
 
         kmer.rotate_right(1);
 
-        Self {
-            kmer_mask: (1 << (k * 2)) - 1,
+        Ok(Self {
+            k,
+            kmer_mask: Vec::new(),
             seq,
             pos: (k - 1) as usize,
             kmer,
             minimizer,
-        }
+        })
+    }
+
+    /// Same as [try_new](Forward::try_new) but also return an error if seq contains a byte that isn't a nucleotide
+    pub fn new_checked(seq: &'a [u8], k: u8, m: u8) -> Result<Self, error::Error> {
+        kmer::validate(seq)?;
+
+        Self::try_new(seq, k, m)
     }
 }
 
@@ -105,7 +151,7 @@ where
     type Item = (Vec<u8>, u64);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos == self.seq.len() {
+        if self.pos >= self.seq.len() {
             None
         } else {
             self.kmer.rotate_left(1);
@@ -121,6 +167,123 @@ where
     }
 }
 
+/// An iterator that takes a DNA sequence and produces kmers (in the forward direction and 2bit form) and the associated minimizer, kmers that contains a byte that isn't a nucleotide are skipped.
+///
+/// Minimizer method is reset after each invalid byte.
+///
+/// # Example
+///
+/// ```
+/// use cocktail::tokenizer::minimizer::ForwardSkipN;
+/// use cocktail::tokenizer::minimizer::method;
+///
+/// let tokenizer = ForwardSkipN::<method::Random>::new(b"GTACTGTGCCCGTGTTACTTNNNNNAAGCGTGAAAGGTGCGTGTTTCCGAGA", 5, 3);
+///
+/// for (kmer, minimizer) in tokenizer {
+///     // ... do what you want ...
+/// }
+pub struct ForwardSkipN<'a, M, K = u64>
+where
+    M: method::Method<K>,
+{
+    k: u8,
+    m: u8,
+    kmer_mask: K,
+    seq: &'a [u8],
+    pos: usize,
+    valid: usize,
+    kmer: K,
+    minimizer: M,
+}
+
+impl<'a, M, K> ForwardSkipN<'a, M, K>
+where
+    M: method::Method<K>,
+    K: kmer::Kmer,
+{
+    /// Create a new ForwardSkipN on seq DNA kmer size is equal to k, minimizer size is equal to m
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=`K::MAX_K` or m isn't in 1..=min(k, 32), see [try_new](ForwardSkipN::try_new)
+    pub fn new(seq: &'a [u8], k: u8, m: u8) -> Self {
+        Self::try_new(seq, k, m).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](ForwardSkipN::new) but return an error if k isn't in 1..=`K::MAX_K` or m isn't in 1..=min(k, 32)
+    pub fn try_new(seq: &'a [u8], k: u8, m: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, K::MAX_K)?;
+        kmer::check_minimizer_size(k.min(32), m)?;
+
+        Ok(Self {
+            k,
+            m,
+            kmer_mask: K::kmer_mask(k),
+            seq,
+            pos: 0,
+            valid: 0,
+            kmer: K::default(),
+            minimizer: M::default(),
+        })
+    }
+}
+
+impl<'a, M, K> Iterator for ForwardSkipN<'a, M, K>
+where
+    M: method::Method<K>,
+    K: kmer::Kmer,
+{
+    type Item = (K, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.seq.len() {
+            let nuc = self.seq[self.pos];
+            self.pos += 1;
+
+            if !kmer::is_nuc(nuc) {
+                self.valid = 0;
+                self.kmer = K::default();
+                continue;
+            }
+
+            self.kmer = ((self.kmer << 2) & self.kmer_mask) | K::nuc2bit(nuc);
+            self.valid += 1;
+
+            if self.valid < self.k as usize {
+                continue;
+            } else if self.valid == self.k as usize {
+                self.minimizer.init(self.k, self.m, self.kmer >> 2);
+            }
+
+            self.minimizer.add_kmer(self.kmer);
+
+            return Some((self.kmer, self.minimizer.get_mini().0));
+        }
+
+        None
+    }
+}
+
+impl<'a, M, K> Position for Forward<'a, M, K>
+where
+    Forward<'a, M, K>: Iterator,
+    M: method::Method<K>,
+{
+    fn position(&self) -> usize {
+        self.pos - self.k as usize
+    }
+}
+
+impl<'a, M, K> Position for ForwardSkipN<'a, M, K>
+where
+    ForwardSkipN<'a, M, K>: Iterator,
+    M: method::Method<K>,
+{
+    fn position(&self) -> usize {
+        self.pos - self.k as usize
+    }
+}
+
 #[cfg(test)]
 mod tests {
     /* crate use */
@@ -264,6 +427,135 @@ mod tests {
         );
     }
 
+    #[test]
+    fn u128() {
+        let mut rng = biotest::rand();
+        let generator = biotest::Sequence::builder()
+            .sequence_len(50)
+            .build()
+            .unwrap();
+        let mut seq = vec![];
+        generator.record(&mut seq, &mut rng).unwrap();
+
+        let small: Vec<(u128, u64)> = Forward::<method::Random, u64>::new(&seq, 11, 7)
+            .map(|(kmer, mini)| (kmer as u128, mini))
+            .collect();
+        let large: Vec<(u128, u64)> = Forward::<method::Random, u128>::new(&seq, 11, 7).collect();
+
+        assert_eq!(small, large);
+
+        let token = Forward::<method::Random, u128>::new(&seq, 41, 15);
+        for (i, (kmer, mini)) in token.enumerate() {
+            assert_eq!(kmer, <u128 as kmer::Kmer>::seq2bit(&seq[i..i + 41]));
+
+            let minis = (i..i + 41 - 15 + 1)
+                .map(|j| crate::kmer::canonical(crate::kmer::seq2bit(&seq[j..j + 15]), 15))
+                .collect::<Vec<u64>>();
+            assert!(minis.contains(&mini));
+        }
+    }
+
+    #[test]
+    fn checked() {
+        assert!(Forward::<method::Random, u64>::new_checked(b"ACTGACTGAC", 5, 3).is_ok());
+        assert!(Forward::<method::Random, Vec<u8>>::new_checked(b"ACTGACTGAC", 5, 3).is_ok());
+
+        assert_eq!(
+            Forward::<method::Random, u64>::new_checked(b"ACTGACNGAC", 5, 3).err(),
+            Some(crate::error::Error::Nucleotide {
+                byte: b'N',
+                position: 6
+            })
+        );
+        assert!(Forward::<method::Random, Vec<u8>>::new_checked(b"ACTGAC-GAC", 5, 3).is_err());
+    }
+
+    #[test]
+    fn skip_n() {
+        let mut rng = biotest::rand();
+        let generator = biotest::Sequence::builder()
+            .sequence_len(50)
+            .build()
+            .unwrap();
+        let mut seq = vec![];
+        generator.record(&mut seq, &mut rng).unwrap();
+
+        assert_eq!(
+            Forward::<method::Random, u64>::new(&seq, 11, 7).collect::<Vec<(u64, u64)>>(),
+            ForwardSkipN::<method::Random, u64>::new(&seq, 11, 7).collect::<Vec<(u64, u64)>>()
+        );
+
+        let mut with_n = seq.clone();
+        with_n[20] = b'N';
+        with_n[25] = b'n';
+
+        let expected = Forward::<method::Random, u64>::new(&seq[..20], 11, 7)
+            .chain(Forward::<method::Random, u64>::new(&seq[26..], 11, 7))
+            .collect::<Vec<(u64, u64)>>();
+
+        assert_eq!(
+            expected,
+            ForwardSkipN::<method::Random, u64>::new(&with_n, 11, 7).collect::<Vec<(u64, u64)>>()
+        );
+    }
+
+    #[test]
+    fn shorter_than_k() {
+        assert!(Forward::<method::Random, u64>::new(b"", 5, 3)
+            .next()
+            .is_none());
+        assert!(Forward::<method::Random, u64>::new(b"ACT", 5, 3)
+            .next()
+            .is_none());
+        assert!(Forward::<method::Random, Vec<u8>>::new(b"ACT", 5, 3)
+            .next()
+            .is_none());
+        assert!(ForwardSkipN::<method::Random, u64>::new(b"ACTG", 5, 3)
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn invalid_size() {
+        assert_eq!(
+            Forward::<method::Random, u64>::try_new(b"ACTGACTG", 0, 3).err(),
+            Some(crate::error::Error::KmerSize { k: 0, max: 32 })
+        );
+        assert_eq!(
+            Forward::<method::Random, u64>::try_new(b"ACTGACTG", 5, 7).err(),
+            Some(crate::error::Error::MinimizerSize { m: 7, k: 5 })
+        );
+        assert_eq!(
+            Forward::<method::Random, u128>::try_new(b"ACTGACTG", 41, 33).err(),
+            Some(crate::error::Error::MinimizerSize { m: 33, k: 32 })
+        );
+        assert!(Forward::<method::Random, Vec<u8>>::try_new(b"ACTGACTG", 5, 0).is_err());
+        assert!(ForwardSkipN::<method::Random, u64>::try_new(b"ACTGACTG", 5, 6).is_err());
+    }
+
+    #[test]
+    fn k_32() {
+        let seq = b"ACTGACTGGATCAATGGACACGTACGATCGATCAG";
+
+        let token = Forward::<method::Random, u64>::new(seq, 32, 32);
+        for (i, (kmer, mini)) in token.enumerate() {
+            assert_eq!(kmer, crate::kmer::seq2bit(&seq[i..i + 32]));
+            assert_eq!(mini, crate::kmer::canonical_min(kmer, 32));
+        }
+    }
+
+    #[test]
+    fn even_m() {
+        let seq = b"ACTGACTGGATCAATGGACACGTACGATCGATCAG";
+
+        for (i, (_, mini)) in Forward::<method::Random, u64>::new(seq, 11, 6).enumerate() {
+            assert_eq!(mini, crate::kmer::canonical_min(mini, 6));
+            assert!(seq[i..i + 11]
+                .windows(6)
+                .any(|w| crate::kmer::canonical_min(crate::kmer::seq2bit(w), 6) == mini));
+        }
+    }
+
     #[test]
     fn same_in_each_strand() {
         let mut rng = biotest::rand();
@@ -305,4 +597,31 @@ mod tests {
             panic!("\nleft: {:?}\nright: {:?}", fwd_minis, rev_minis);
         }
     }
+
+    #[test]
+    fn positions() {
+        let seq = b"ACTGACTGGANCAATGGACACGT";
+
+        assert_eq!(
+            (0..12).collect::<Vec<usize>>(),
+            Forward::<method::Random, u64>::new(&seq[..16], 5, 3)
+                .positions()
+                .map(|(pos, _)| pos)
+                .collect::<Vec<usize>>()
+        );
+        assert_eq!(
+            (0..12).collect::<Vec<usize>>(),
+            Forward::<method::Random, Vec<u8>>::new(&seq[..16], 5, 3)
+                .positions()
+                .map(|(pos, _)| pos)
+                .collect::<Vec<usize>>()
+        );
+        assert_eq!(
+            vec![0, 1, 2, 3, 4, 5, 11, 12, 13, 14, 15, 16, 17, 18],
+            ForwardSkipN::<method::Random, u64>::new(seq, 5, 3)
+                .positions()
+                .map(|(pos, _)| pos)
+                .collect::<Vec<usize>>()
+        );
+    }
 }
@@ -39,6 +39,11 @@ impl Random {
         self.minimizer = index;
     }
 
+    /// Canonical form of subkmer, parity is used if m is odd, smallest value if m is even
+    fn canonical(&self, subkmer: u64) -> u64 {
+        kmer::canonical_any(subkmer, self.m)
+    }
+
     fn get_score(x: u64) -> u64 {
         x.rotate_left(5)
             .bitxor(x)
@@ -46,13 +51,16 @@ impl Random {
     }
 }
 
-impl method::Method<u64> for Random {
+impl<K> method::Method<K> for Random
+where
+    K: kmer::Kmer,
+{
     /// Create a MinimizerRing, with kmer size equale to `k`, subkmer size equale to `m` and init ring buffer with `kmer`
-    fn init(&mut self, k: u8, m: u8, mut kmer: u64) {
+    fn init(&mut self, k: u8, m: u8, mut kmer: K) {
         self.ring_buffer = vec![(0, 0); (k - m + 1) as usize].into_boxed_slice();
         self.current = 0;
         self.minimizer = 0;
-        self.mask = (1 << (m * 2)) - 1;
+        self.mask = u64::MAX >> (64 - m * 2);
         self.k = k;
         self.m = m;
 
@@ -63,7 +71,7 @@ impl method::Method<u64> for Random {
         for i in 0..max_len {
             let rb_index = max_len - i - 1;
 
-            let mini = kmer::canonical(kmer & self.mask, self.m);
+            let mini = self.canonical(kmer.as_u64() & self.mask);
 
             let local_score = Random::get_score(mini);
             self.ring_buffer[rb_index] = (mini, local_score);
@@ -73,18 +81,18 @@ impl method::Method<u64> for Random {
                 self.minimizer = rb_index;
             }
 
-            kmer >>= 2;
+            kmer = kmer >> 2;
         }
 
         self.current = 0;
     }
 
     /// Add the next kmer
-    fn add_kmer(&mut self, kmer: u64) {
-        let minimizer = kmer::canonical(kmer & self.mask, self.m);
+    fn add_kmer(&mut self, kmer: K) {
+        let minimizer = self.canonical(kmer.as_u64() & self.mask);
         let score = Random::get_score(minimizer);
 
-        let previous_mini = <Random as method::Method<u64>>::get_mini(self);
+        let previous_mini = <Random as method::Method<K>>::get_mini(self);
         self.ring_buffer[self.current] = (minimizer, score);
 
         if score < previous_mini.1 {
@@ -108,7 +116,7 @@ impl method::Method<Vec<u8>> for Random {
         self.ring_buffer = vec![(0, 0); (k - m + 1) as usize].into_boxed_slice();
         self.current = 0;
         self.minimizer = 0;
-        self.mask = (1 << (m * 2)) - 1;
+        self.mask = u64::MAX >> (64 - m * 2);
         self.k = k;
         self.m = m;
 
@@ -118,7 +126,7 @@ impl method::Method<Vec<u8>> for Random {
 
         for i in 0..max_len - 1 {
             let rb_index = i + 1;
-            let mini = kmer::canonical(kmer::seq2bit(&kmer[i..i + self.m as usize]), self.m);
+            let mini = self.canonical(kmer::seq2bit(&kmer[i..i + self.m as usize]));
 
             let local_score = Random::get_score(mini);
             self.ring_buffer[rb_index] = (mini, local_score);
@@ -134,7 +142,7 @@ impl method::Method<Vec<u8>> for Random {
 
     /// Add the next kmer
     fn add_kmer(&mut self, kmer: Vec<u8>) {
-        let minimizer = kmer::canonical(kmer::seq2bit(&kmer[(self.k - self.m) as usize..]), self.m);
+        let minimizer = self.canonical(kmer::seq2bit(&kmer[(self.k - self.m) as usize..]));
         let score = Random::get_score(minimizer);
 
         let previous_mini = <Random as method::Method<Vec<u8>>>::get_mini(self);
@@ -0,0 +1,279 @@
+//! This module provides iterator to produce kmer from a [PackedSeq](crate::packed::PackedSeq)
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+use crate::error;
+use crate::kmer;
+use crate::packed::PackedSeq;
+use crate::tokenizer::position::{Orientation, Position, Strand};
+use crate::tokenizer::typed::Typed;
+
+/// An iterator that takes a [PackedSeq] and produces kmers, in the forward orientation and 2bit form.
+///
+/// Produce same kmers as [kmer::Forward](crate::tokenizer::kmer::Forward) on the unpacked sequence.
+///
+/// # Example
+///
+/// ```
+/// use cocktail::packed::PackedSeq;
+/// use cocktail::tokenizer::packed::Forward;
+///
+/// let seq = PackedSeq::from_bytes(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA");
+/// let tokenizer = Forward::<u64>::new(&seq, 5);
+///
+/// for kmer in tokenizer {
+///     // ... do what you want ...
+/// }
+pub struct Forward<'a, K> {
+    k: usize,
+    kmer_mask: K,
+    seq: &'a PackedSeq,
+    pos: usize,
+    kmer: K,
+}
+
+impl<'a, K> Forward<'a, K>
+where
+    K: kmer::Kmer,
+{
+    /// Create a new Forward on seq DNA kmer size is equal to k, if seq is shorter than k no kmer is produce
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=`K::MAX_K`, see [try_new](Forward::try_new)
+    pub fn new(seq: &'a PackedSeq, k: u8) -> Self {
+        Self::try_new(seq, k).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](Forward::new) but return an error if k isn't in 1..=`K::MAX_K`
+    pub fn try_new(seq: &'a PackedSeq, k: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, K::MAX_K)?;
+
+        Ok(Forward {
+            k: k as usize,
+            kmer_mask: K::kmer_mask(k),
+            seq,
+            pos: (k - 1) as usize,
+            kmer: prefix(seq, k),
+        })
+    }
+
+    /// Create a new Forward on seq DNA, kmer size `N` is fixed at compile time and kmers are produce as [typed::Kmer](crate::typed::Kmer)
+    pub fn typed<const N: u8>(seq: &'a PackedSeq) -> Typed<Self, N> {
+        Typed::new(Self::new(seq, N))
+    }
+}
+
+impl<'a, K> Iterator for Forward<'a, K>
+where
+    K: kmer::Kmer,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.seq.len() {
+            None
+        } else {
+            self.kmer =
+                ((self.kmer << 2) & self.kmer_mask) | K::from(self.seq.bits(self.pos) as u8);
+
+            self.pos += 1;
+
+            Some(self.kmer)
+        }
+    }
+}
+
+/// An iterator that takes a [PackedSeq] and produces kmers, in the canonical orientation and 2bit form.
+///
+/// Produce same kmers as [kmer::Canonical](crate::tokenizer::kmer::Canonical) on the unpacked sequence.
+///
+/// # Example
+///
+/// ```
+/// use cocktail::packed::PackedSeq;
+/// use cocktail::tokenizer::packed::Canonical;
+///
+/// let seq = PackedSeq::from_bytes(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA");
+/// let tokenizer = Canonical::<u64>::new(&seq, 5);
+///
+/// for kmer in tokenizer {
+///     // ... do what you want ...
+/// }
+pub struct Canonical<'a, K> {
+    k: usize,
+    parity: bool,
+    forward: bool,
+    move_bit: u8,
+    kmer_mask: K,
+    seq: &'a PackedSeq,
+    pos: usize,
+    kmers: [K; 2],
+}
+
+impl<'a, K> Canonical<'a, K>
+where
+    K: kmer::Kmer,
+{
+    /// Create a new Canonical tokenizer on seq DNA, kmer size is equal to k, if seq is shorter than k no kmer is produce
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=`K::MAX_K`, see [try_new](Canonical::try_new)
+    pub fn new(seq: &'a PackedSeq, k: u8) -> Self {
+        Self::try_new(seq, k).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](Canonical::new) but return an error if k isn't in 1..=`K::MAX_K`
+    pub fn try_new(seq: &'a PackedSeq, k: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, K::MAX_K)?;
+
+        let forward: K = prefix(seq, k);
+
+        Ok(Canonical {
+            k: k as usize,
+            parity: k % 2 == 1,
+            forward: true,
+            move_bit: (k - 1) * 2,
+            kmer_mask: K::kmer_mask(k),
+            seq,
+            pos: (k - 1) as usize,
+            kmers: [forward, forward.revcomp(k)],
+        })
+    }
+
+    /// Create a new Canonical tokenizer on seq DNA, kmer size `N` is fixed at compile time and kmers are produce as [typed::Kmer](crate::typed::Kmer)
+    pub fn typed<const N: u8>(seq: &'a PackedSeq) -> Typed<Self, N> {
+        Typed::new(Self::new(seq, N))
+    }
+}
+
+impl<'a, K> Iterator for Canonical<'a, K>
+where
+    K: kmer::Kmer,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.seq.len() {
+            None
+        } else {
+            let nuc = K::from(self.seq.bits(self.pos) as u8);
+            self.pos += 1;
+
+            self.kmers[0] = ((self.kmers[0] << 2) & self.kmer_mask) | nuc;
+            self.kmers[1] = (self.kmers[1] >> 2) ^ ((nuc ^ K::from(0b10)) << self.move_bit);
+
+            self.forward = if self.parity {
+                self.kmers[0].parity_even()
+            } else {
+                self.kmers[0] <= self.kmers[1]
+            };
+
+            if self.forward {
+                Some(self.kmers[0])
+            } else {
+                Some(self.kmers[1])
+            }
+        }
+    }
+}
+
+impl<'a, K> Position for Forward<'a, K>
+where
+    K: kmer::Kmer,
+{
+    fn position(&self) -> usize {
+        self.pos - self.k
+    }
+}
+
+impl<'a, K> Position for Canonical<'a, K>
+where
+    K: kmer::Kmer,
+{
+    fn position(&self) -> usize {
+        self.pos - self.k
+    }
+}
+
+impl<'a, K> Orientation for Canonical<'a, K>
+where
+    K: kmer::Kmer,
+{
+    fn strand(&self) -> Strand {
+        if self.forward {
+            Strand::Forward
+        } else {
+            Strand::Reverse
+        }
+    }
+}
+
+/// Build the 2 bit representation of the k - 1 first nucleotides of seq
+fn prefix<K>(seq: &PackedSeq, k: u8) -> K
+where
+    K: kmer::Kmer,
+{
+    (0..(k as usize - 1).min(seq.len())).fold(K::default(), |kmer, pos| {
+        (kmer << 2) | K::from(seq.bits(pos) as u8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    /* project use */
+    use super::*;
+    use crate::test_utils::random_seq;
+    use crate::tokenizer;
+
+    #[test]
+    fn forward() {
+        let seq = random_seq(150);
+        let packed = PackedSeq::from_bytes(&seq);
+
+        for k in [1, 5, 32] {
+            assert_eq!(
+                tokenizer::kmer::Forward::<u64>::new(&seq, k).collect::<Vec<u64>>(),
+                Forward::<u64>::new(&packed, k).collect::<Vec<u64>>()
+            );
+        }
+
+        assert_eq!(
+            tokenizer::kmer::Forward::<u128>::new_generic(&seq, 63).collect::<Vec<u128>>(),
+            Forward::<u128>::new(&packed, 63).collect::<Vec<u128>>()
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        let seq = random_seq(150);
+        let packed = PackedSeq::from_bytes(&seq);
+
+        for k in [1, 5, 6, 32] {
+            assert_eq!(
+                tokenizer::kmer::Canonical::<u64>::new(&seq, k)
+                    .stranded()
+                    .collect::<Vec<(usize, u64, Strand)>>(),
+                Canonical::<u64>::new(&packed, k)
+                    .stranded()
+                    .collect::<Vec<(usize, u64, Strand)>>()
+            );
+        }
+    }
+
+    #[test]
+    fn shorter_than_k() {
+        let packed = PackedSeq::from_bytes(b"ACTG");
+
+        assert!(Forward::<u64>::new(&packed, 5).next().is_none());
+        assert!(Canonical::<u64>::new(&PackedSeq::new(), 5).next().is_none());
+        assert_eq!(
+            Forward::<u64>::try_new(&packed, 33).err(),
+            Some(error::Error::KmerSize { k: 33, max: 32 })
+        );
+    }
+}
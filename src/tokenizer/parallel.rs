@@ -0,0 +1,309 @@
+//! This module provides function to tokenize one long sequence on multiple threads.
+//!
+//! Sequence is split in chunks that overlap by k - 1 nucleotides, so each kmer is produce by exactly one chunk.
+//! For minimizer tokenizers chunks also overlap by the minimizer window (k - m + 1 nucleotides), each chunk except the first one begin a window earlier and the kmers of this window are skipped, so minimizer tokenizer is in the same state than on the whole sequence at the first kmer of chunk.
+
+/* standard use */
+use std::ops::Range;
+
+/* crates use */
+
+/* project use */
+use crate::kmer;
+use crate::tokenizer;
+use crate::tokenizer::minimizer::method;
+
+/// Tokenize seq with [kmer::Canonical](tokenizer::kmer::Canonical) on threads, kmers are return in sequence order
+///
+/// # Panics
+///
+/// If k isn't in 1..=`K::MAX_K`
+pub fn canonical<K>(seq: &[u8], k: u8, threads: usize) -> Vec<K>
+where
+    K: kmer::Kmer + Send,
+{
+    kmer::check_kmer_size(k, K::MAX_K).unwrap_or_else(|e| panic!("{}", e));
+
+    let mut chunks: Vec<Vec<K>> = Vec::new();
+
+    std::thread::scope(|scope| {
+        let handles = split(seq.len(), k, threads)
+            .into_iter()
+            .map(|range| {
+                let chunk = &seq[range];
+                scope
+                    .spawn(move || tokenizer::kmer::Canonical::<K>::new_generic(chunk, k).collect())
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            chunks.push(join(handle));
+        }
+    });
+
+    chunks.concat()
+}
+
+/// Tokenize seq with [kmer::Canonical](tokenizer::kmer::Canonical) on threads, sink is call in each thread with the position of first kmer of chunk and tokenizer of chunk
+///
+/// # Panics
+///
+/// If k isn't in 1..=`K::MAX_K`
+pub fn for_each_canonical<K, F>(seq: &[u8], k: u8, threads: usize, sink: F)
+where
+    K: kmer::Kmer,
+    F: Fn(usize, tokenizer::kmer::Canonical<'_, K>) + Sync,
+{
+    kmer::check_kmer_size(k, K::MAX_K).unwrap_or_else(|e| panic!("{}", e));
+
+    let sink = &sink;
+
+    std::thread::scope(|scope| {
+        for range in split(seq.len(), k, threads) {
+            let start = range.start;
+            let chunk = &seq[range];
+            scope.spawn(move || {
+                sink(
+                    start,
+                    tokenizer::kmer::Canonical::<K>::new_generic(chunk, k),
+                )
+            });
+        }
+    });
+}
+
+/// Tokenize seq with [minimizer::Canonical](tokenizer::minimizer::Canonical) on threads, kmers and minimizers are return in sequence order
+///
+/// # Panics
+///
+/// If k isn't in 1..=`K::MAX_K` or m isn't in 1..=min(k, 32)
+pub fn minimizer<M, K>(seq: &[u8], k: u8, m: u8, threads: usize) -> Vec<(K, u64)>
+where
+    M: method::Method<K>,
+    K: kmer::Kmer + Send,
+{
+    kmer::check_kmer_size(k, K::MAX_K).unwrap_or_else(|e| panic!("{}", e));
+    kmer::check_minimizer_size(k.min(32), m).unwrap_or_else(|e| panic!("{}", e));
+
+    let mut chunks: Vec<Vec<(K, u64)>> = Vec::new();
+
+    std::thread::scope(|scope| {
+        let handles = split_window(seq.len(), k, m, threads)
+            .into_iter()
+            .map(|(range, skip)| {
+                let chunk = &seq[range];
+                scope.spawn(move || {
+                    tokenizer::minimizer::Canonical::<M, K>::new(chunk, k, m)
+                        .skip(skip)
+                        .collect()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            chunks.push(join(handle));
+        }
+    });
+
+    chunks.concat()
+}
+
+/// Tokenize seq with [minimizer::Canonical](tokenizer::minimizer::Canonical) on threads, sink is call in each thread with the position of first kmer of chunk and tokenizer of chunk
+///
+/// # Panics
+///
+/// If k isn't in 1..=`K::MAX_K` or m isn't in 1..=min(k, 32)
+pub fn for_each_minimizer<M, K, F>(seq: &[u8], k: u8, m: u8, threads: usize, sink: F)
+where
+    M: method::Method<K>,
+    K: kmer::Kmer,
+    F: Fn(usize, tokenizer::minimizer::Canonical<'_, M, K>) + Sync,
+{
+    kmer::check_kmer_size(k, K::MAX_K).unwrap_or_else(|e| panic!("{}", e));
+    kmer::check_minimizer_size(k.min(32), m).unwrap_or_else(|e| panic!("{}", e));
+
+    let sink = &sink;
+
+    std::thread::scope(|scope| {
+        for (range, skip) in split_window(seq.len(), k, m, threads) {
+            let start = range.start + skip;
+            let chunk = &seq[range];
+            scope.spawn(move || {
+                let mut tokenizer = tokenizer::minimizer::Canonical::<M, K>::new(chunk, k, m);
+                if skip != 0 {
+                    tokenizer.nth(skip - 1);
+                }
+
+                sink(start, tokenizer)
+            });
+        }
+    });
+}
+
+/// Split a sequence of length len in at most threads ranges that overlap by k - 1 nucleotides
+fn split(len: usize, k: u8, threads: usize) -> Vec<Range<usize>> {
+    let overlap = (k as usize).saturating_sub(1);
+    let nb_kmer = len.saturating_sub(overlap);
+    let threads = threads.clamp(1, nb_kmer.max(1));
+    let by_chunk = nb_kmer.div_ceil(threads).max(1);
+
+    (0..threads)
+        .map(|i| {
+            let begin = (i * by_chunk).min(nb_kmer);
+            let end = ((i + 1) * by_chunk).min(nb_kmer);
+
+            begin..(end + overlap).min(len)
+        })
+        .filter(|range| range.len() > overlap || range.start == 0)
+        .collect()
+}
+
+/// Same as [split] but each range except the first one begin the minimizer window (k - m + 1 nucleotides) earlier, return range and number of kmers to skip
+fn split_window(len: usize, k: u8, m: u8, threads: usize) -> Vec<(Range<usize>, usize)> {
+    let window = (k - m + 1) as usize;
+
+    split(len, k, threads)
+        .into_iter()
+        .map(|range| {
+            let begin = range.start.saturating_sub(window);
+
+            (begin..range.end, range.start - begin)
+        })
+        .collect()
+}
+
+fn join<T>(handle: std::thread::ScopedJoinHandle<'_, T>) -> T {
+    handle
+        .join()
+        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+}
+
+#[cfg(test)]
+mod tests {
+    /* project use */
+    use super::*;
+    use crate::test_utils::random_seq;
+
+    #[test]
+    fn split_() {
+        assert_eq!(split(10, 3, 2), vec![0..6, 4..10]);
+        assert_eq!(split(10, 3, 3), vec![0..5, 3..8, 6..10]);
+        assert_eq!(
+            split(10, 3, 20),
+            (0..8).map(|i| i..i + 3).collect::<Vec<_>>()
+        );
+        assert_eq!(split(2, 3, 4), vec![0..2]);
+        assert_eq!(split(0, 1, 4), vec![0..0]);
+    }
+
+    #[test]
+    fn split_window_() {
+        assert_eq!(split_window(10, 3, 2, 2), vec![(0..6, 0), (2..10, 2)]);
+        assert_eq!(
+            split_window(10, 3, 3, 3),
+            vec![(0..5, 0), (2..8, 1), (5..10, 1)]
+        );
+        assert_eq!(split_window(2, 3, 2, 4), vec![(0..2, 0)]);
+    }
+
+    #[test]
+    fn canonical_() {
+        let seq = random_seq(500);
+
+        for k in [1, 5, 6, 31] {
+            for threads in [0, 1, 3, 8] {
+                assert_eq!(
+                    tokenizer::kmer::Canonical::<u64>::new(&seq, k).collect::<Vec<u64>>(),
+                    canonical::<u64>(&seq, k, threads)
+                );
+            }
+        }
+
+        assert!(canonical::<u64>(b"ACTG", 5, 4).is_empty());
+    }
+
+    #[test]
+    fn for_each_canonical_() {
+        let seq = random_seq(500);
+        let kmers = std::sync::Mutex::new(Vec::new());
+
+        for_each_canonical::<u64, _>(&seq, 11, 4, |start, tokenizer| {
+            let local = tokenizer
+                .enumerate()
+                .map(|(i, kmer)| (start + i, kmer))
+                .collect::<Vec<(usize, u64)>>();
+
+            kmers.lock().unwrap().extend(local);
+        });
+
+        let mut kmers = kmers.into_inner().unwrap();
+        kmers.sort();
+
+        assert_eq!(
+            tokenizer::kmer::Canonical::<u64>::new(&seq, 11)
+                .enumerate()
+                .collect::<Vec<(usize, u64)>>(),
+            kmers
+        );
+    }
+
+    #[test]
+    fn minimizer_() {
+        let seq = random_seq(500);
+
+        for (k, m) in [(11, 7), (12, 6), (31, 15)] {
+            for threads in [1, 3, 8] {
+                assert_eq!(
+                    tokenizer::minimizer::Canonical::<method::Random, u64>::new(&seq, k, m)
+                        .collect::<Vec<(u64, u64)>>(),
+                    minimizer::<method::Random, u64>(&seq, k, m, threads)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn minimizer_chunk_boundary() {
+        // 90 kmers on 3 threads, chunks begin at kmer 30 and 60 inside minimizer window of 5 nucleotides
+        let seq = random_seq(100);
+        assert_eq!(
+            split_window(seq.len(), 11, 7, 3),
+            vec![(0..40, 0), (25..70, 5), (55..100, 5)]
+        );
+
+        let single = tokenizer::minimizer::Canonical::<method::Random, u64>::new(&seq, 11, 7)
+            .enumerate()
+            .collect::<Vec<(usize, (u64, u64))>>();
+
+        assert_eq!(
+            single.iter().map(|(_, x)| *x).collect::<Vec<(u64, u64)>>(),
+            minimizer::<method::Random, u64>(&seq, 11, 7, 3)
+        );
+
+        let kmers = std::sync::Mutex::new(Vec::new());
+        for_each_minimizer::<method::Random, u64, _>(&seq, 11, 7, 3, |start, tokenizer| {
+            let local = tokenizer
+                .enumerate()
+                .map(|(i, x)| (start + i, x))
+                .collect::<Vec<(usize, (u64, u64))>>();
+
+            kmers.lock().unwrap().extend(local);
+        });
+        let mut kmers = kmers.into_inner().unwrap();
+        kmers.sort();
+
+        assert_eq!(single, kmers);
+    }
+
+    #[test]
+    fn for_each_minimizer_() {
+        let seq = random_seq(500);
+        let count = std::sync::atomic::AtomicUsize::new(0);
+
+        for_each_minimizer::<method::Random, u64, _>(&seq, 11, 7, 4, |_, tokenizer| {
+            count.fetch_add(tokenizer.count(), std::sync::atomic::Ordering::Relaxed);
+        });
+
+        assert_eq!(count.into_inner(), 500 - 11 + 1);
+    }
+}
@@ -0,0 +1,107 @@
+//! Traits and iterator adaptors to get position, and strand for canonical tokenizer, of each kmer produce by a tokenizer
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+
+/// Strand of sequence where a canonical kmer come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strand {
+    /// Canonical kmer is the forward kmer, if kmer is is own reverse complement strand is Forward
+    Forward,
+
+    /// Canonical kmer is the reverse complement of forward kmer
+    Reverse,
+}
+
+/// A tokenizer that know position of the last kmer it produce
+pub trait Position: Iterator {
+    /// Return the position, in input sequence, of the first nucleotide of the last kmer produce by [next](Iterator::next)
+    fn position(&self) -> usize;
+
+    /// Create an iterator that produce position and kmer
+    fn positions(self) -> Positions<Self>
+    where
+        Self: Sized,
+    {
+        Positions { tokenizer: self }
+    }
+}
+
+/// A canonical tokenizer that know the strand of the last kmer it produce
+pub trait Orientation: Position {
+    /// Return the strand of the last kmer produce by [next](Iterator::next)
+    fn strand(&self) -> Strand;
+
+    /// Create an iterator that produce position, kmer and strand
+    fn stranded(self) -> Stranded<Self>
+    where
+        Self: Sized,
+    {
+        Stranded { tokenizer: self }
+    }
+}
+
+/// An iterator that wraps a tokenizer and produces `(position, kmer)`, build it with [positions](Position::positions).
+///
+/// # Example
+///
+/// ```
+/// use cocktail::tokenizer::kmer::Forward;
+/// use cocktail::tokenizer::position::Position as _;
+///
+/// let tokenizer = Forward::<u64>::new(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA", 5);
+///
+/// for (position, kmer) in tokenizer.positions() {
+///     // ... do what you want ...
+/// }
+/// ```
+pub struct Positions<I> {
+    tokenizer: I,
+}
+
+impl<I> Iterator for Positions<I>
+where
+    I: Position,
+{
+    type Item = (usize, I::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tokenizer
+            .next()
+            .map(|kmer| (self.tokenizer.position(), kmer))
+    }
+}
+
+/// An iterator that wraps a canonical tokenizer and produces `(position, kmer, strand)`, build it with [stranded](Orientation::stranded).
+///
+/// # Example
+///
+/// ```
+/// use cocktail::tokenizer::kmer::Canonical;
+/// use cocktail::tokenizer::position::{Orientation as _, Strand};
+///
+/// let tokenizer = Canonical::<u64>::new(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA", 5);
+///
+/// for (position, kmer, strand) in tokenizer.stranded() {
+///     // ... do what you want ...
+/// }
+/// ```
+pub struct Stranded<I> {
+    tokenizer: I,
+}
+
+impl<I> Iterator for Stranded<I>
+where
+    I: Orientation,
+{
+    type Item = (usize, I::Item, Strand);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tokenizer
+            .next()
+            .map(|kmer| (self.tokenizer.position(), kmer, self.tokenizer.strand()))
+    }
+}
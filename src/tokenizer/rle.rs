@@ -7,11 +7,15 @@
 /* project use */
 
 /* module declaration */
+use crate::error;
 use crate::kmer;
 use crate::rle;
+use crate::tokenizer::position::Position;
 
 /// An iterator that takes a DNA sequence and produces kmers, in the forward orientation and 2bit form, homopolymer are compacted.
 ///
+/// [position](Position::position) is the position in the input sequence, not in the compacted sequence.
+///
 /// # Example
 ///
 /// ```
@@ -23,21 +27,49 @@ use crate::rle;
 ///     // ... do what you want ...
 /// }
 pub struct Forward {
+    k: usize,
     kmer_mask: u64,
     seq: Box<[u8]>,
     pos: usize,
+    start: usize,
     kmer: u64,
 }
 
 impl Forward {
-    /// Create a new TokenizerRLE on seq DNA kmer size is equal to k
+    /// Create a new TokenizerRLE on seq DNA kmer size is equal to k, if compacted seq is shorter than k no kmer is produce
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=32, see [try_new](Forward::try_new)
     pub fn new(seq: &[u8], k: u8) -> Self {
-        Forward {
-            kmer_mask: (1 << (k * 2)) - 1,
-            seq: rle::seq2rle(seq),
+        Self::try_new(seq, k).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](Forward::new) but return an error if k isn't in 1..=32
+    pub fn try_new(seq: &[u8], k: u8) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, 32)?;
+
+        let rles = rle::seq2rle(seq);
+        let kmer = rles
+            .iter()
+            .take((k - 1) as usize)
+            .fold(0, |kmer, rle| (kmer << 2) | rle::rle2bit(*rle));
+
+        Ok(Forward {
+            k: k as usize,
+            kmer_mask: u64::MAX >> (64 - k * 2),
+            seq: rles,
             pos: (k - 1) as usize,
-            kmer: kmer::seq2bit(unsafe { seq.get_unchecked(0..((k - 1) as usize)) }),
-        }
+            start: 0,
+            kmer,
+        })
+    }
+
+    /// Same as [try_new](Forward::try_new) but also return an error if seq contains a byte that isn't a nucleotide
+    pub fn new_checked(seq: &[u8], k: u8) -> Result<Self, error::Error> {
+        kmer::validate(seq)?;
+
+        Self::try_new(seq, k)
     }
 }
 
@@ -45,11 +77,15 @@ impl Iterator for Forward {
     type Item = u64;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos == self.seq.len() {
+        if self.pos >= self.seq.len() {
             None
         } else {
             self.kmer = ((self.kmer << 2) & self.kmer_mask) | rle::rle2bit(self.seq[self.pos]);
 
+            if self.pos >= self.k {
+                self.start += rle::rle2len(self.seq[self.pos - self.k]);
+            }
+
             self.pos += 1;
 
             Some(self.kmer)
@@ -57,6 +93,12 @@ impl Iterator for Forward {
     }
 }
 
+impl Position for Forward {
+    fn position(&self) -> usize {
+        self.start
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -88,4 +130,48 @@ mod tests {
                 .collect::<Vec<u64>>()
         );
     }
+
+    #[test]
+    fn homopolymer() {
+        assert_eq!(
+            Forward::new(b"ACTGACTG", 5).collect::<Vec<u64>>(),
+            Forward::new(b"AACCCTGAAACTGG", 5).collect::<Vec<u64>>()
+        );
+    }
+
+    #[test]
+    fn first_kmer_from_compacted() {
+        // first k - 1 nucleotides are read in compacted sequence, not in input sequence
+        assert_eq!(
+            vec![108, 433, 710, 795],
+            Forward::new(b"AAAACTGACTG", 5).collect::<Vec<u64>>()
+        );
+        assert_eq!(
+            vec![
+                kmer::seq2bit(b"GTA"),
+                kmer::seq2bit(b"TAC"),
+                kmer::seq2bit(b"ACT"),
+                kmer::seq2bit(b"CTG")
+            ],
+            Forward::new(b"GGGGTTTTACTG", 3).collect::<Vec<u64>>()
+        );
+    }
+
+    #[test]
+    fn shorter_than_k() {
+        assert!(Forward::new(b"", 5).next().is_none());
+        assert!(Forward::new(b"AAACCCTTG", 5).next().is_none());
+        assert!(Forward::try_new(b"ACTG", 0).is_err());
+    }
+
+    #[test]
+    fn positions() {
+        assert_eq!(
+            vec![0, 3, 4, 6, 7, 10],
+            Forward::new(b"AAACTTGAAACTGG", 3)
+                .positions()
+                .map(|(pos, _)| pos)
+                .collect::<Vec<usize>>()
+        );
+    }
 }
@@ -0,0 +1,336 @@
+//! This module provides iterator to produce kmer from a DNA sequence read by block from any [Read](std::io::Read).
+//!
+//! Input is read as raw sequence, no byte is skipped, so kmers are the same as produce by [kmer::Canonical](crate::tokenizer::kmer::Canonical) and [minimizer::Canonical](crate::tokenizer::minimizer::Canonical) on whole input.
+//! Rolling kmers and minimizer method state are keep between blocks.
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+use crate::error;
+use crate::kmer;
+use crate::tokenizer::minimizer::method;
+
+/// Default size of block read from input
+pub const BLOCK_SIZE: usize = 1 << 16;
+
+/// A buffer that read input by block and produce each byte
+struct Block<R> {
+    input: R,
+    buffer: Box<[u8]>,
+    len: usize,
+    pos: usize,
+}
+
+impl<R> Block<R>
+where
+    R: std::io::Read,
+{
+    fn new(input: R, block_size: usize) -> Self {
+        Block {
+            input,
+            buffer: vec![0; block_size.max(1)].into_boxed_slice(),
+            len: 0,
+            pos: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn next_byte(&mut self) -> Option<Result<u8, error::Error>> {
+        if self.pos == self.len {
+            self.pos = 0;
+            self.len = loop {
+                match self.input.read(&mut self.buffer) {
+                    Ok(len) => break len,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        self.len = 0;
+                        return Some(Err(e.into()));
+                    }
+                }
+            };
+
+            if self.len == 0 {
+                return None;
+            }
+        }
+
+        let byte = self.buffer[self.pos];
+        self.pos += 1;
+
+        Some(Ok(byte))
+    }
+}
+
+/// Rolling forward and reverse kmers
+struct Rolling<K> {
+    k: u8,
+    parity: bool,
+    move_bit: u8,
+    kmer_mask: K,
+    seen: usize,
+    kmers: [K; 2],
+}
+
+impl<K> Rolling<K>
+where
+    K: kmer::Kmer,
+{
+    fn new(k: u8) -> Self {
+        Rolling {
+            k,
+            parity: k % 2 == 1,
+            move_bit: (k - 1) * 2,
+            kmer_mask: K::kmer_mask(k),
+            seen: 0,
+            kmers: [K::default(), K::default()],
+        }
+    }
+
+    /// Add a nucleotide and return true if k nucleotides have been seen
+    #[inline(always)]
+    fn push(&mut self, nuc: u8) -> bool {
+        let nuc = K::nuc2bit(nuc);
+
+        self.kmers[0] = ((self.kmers[0] << 2) & self.kmer_mask) | nuc;
+        self.kmers[1] = (self.kmers[1] >> 2) ^ ((nuc ^ K::from(0b10)) << self.move_bit);
+        self.seen += 1;
+
+        self.seen >= self.k as usize
+    }
+
+    #[inline(always)]
+    fn canonical(&self) -> K {
+        if self.parity {
+            if self.kmers[0].parity_even() {
+                self.kmers[0]
+            } else {
+                self.kmers[1]
+            }
+        } else {
+            self.kmers[0].min(self.kmers[1])
+        }
+    }
+}
+
+/// An iterator that read a DNA sequence by block and produces kmers, in the canonical orientation and 2bit form.
+///
+/// # Example
+///
+/// ```
+/// use cocktail::tokenizer::stream::Canonical;
+///
+/// let input = std::io::Cursor::new(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA");
+///
+/// for kmer in Canonical::<_, u64>::new(input, 5) {
+///     let kmer = kmer.unwrap();
+///     // ... do what you want ...
+/// }
+/// ```
+pub struct Canonical<R, K> {
+    block: Block<R>,
+    rolling: Rolling<K>,
+}
+
+impl<R, K> Canonical<R, K>
+where
+    R: std::io::Read,
+    K: kmer::Kmer,
+{
+    /// Create a new Canonical tokenizer on input, kmer size is equal to k, input is read by block of [BLOCK_SIZE] bytes
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=`K::MAX_K`, see [try_new](Canonical::try_new)
+    pub fn new(input: R, k: u8) -> Self {
+        Self::try_new(input, k, BLOCK_SIZE).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](Canonical::new) but with a custom block size and return an error if k isn't in 1..=`K::MAX_K`
+    pub fn try_new(input: R, k: u8, block_size: usize) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, K::MAX_K)?;
+
+        Ok(Canonical {
+            block: Block::new(input, block_size),
+            rolling: Rolling::new(k),
+        })
+    }
+}
+
+impl<R, K> Iterator for Canonical<R, K>
+where
+    R: std::io::Read,
+    K: kmer::Kmer,
+{
+    type Item = Result<K, error::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let nuc = match self.block.next_byte()? {
+                Ok(nuc) => nuc,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if self.rolling.push(nuc) {
+                return Some(Ok(self.rolling.canonical()));
+            }
+        }
+    }
+}
+
+/// An iterator that read a DNA sequence by block and produces kmers (in the canonical direction and 2bit form) and the associated minimizer.
+///
+/// # Example
+///
+/// ```
+/// use cocktail::tokenizer::minimizer::method;
+/// use cocktail::tokenizer::stream::Minimizer;
+///
+/// let input = std::io::Cursor::new(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA");
+///
+/// for result in Minimizer::<_, method::Random, u64>::new(input, 11, 7) {
+///     let (kmer, minimizer) = result.unwrap();
+///     // ... do what you want ...
+/// }
+/// ```
+pub struct Minimizer<R, M, K> {
+    block: Block<R>,
+    rolling: Rolling<K>,
+    m: u8,
+    minimizer: M,
+}
+
+impl<R, M, K> Minimizer<R, M, K>
+where
+    R: std::io::Read,
+    M: method::Method<K>,
+    K: kmer::Kmer,
+{
+    /// Create a new Minimizer tokenizer on input, kmer size is equal to k, minimizer size is equal to m, input is read by block of [BLOCK_SIZE] bytes
+    ///
+    /// # Panics
+    ///
+    /// If k isn't in 1..=`K::MAX_K` or m isn't in 1..=min(k, 32), see [try_new](Minimizer::try_new)
+    pub fn new(input: R, k: u8, m: u8) -> Self {
+        Self::try_new(input, k, m, BLOCK_SIZE).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Same as [new](Minimizer::new) but with a custom block size and return an error if k isn't in 1..=`K::MAX_K` or m isn't in 1..=min(k, 32)
+    pub fn try_new(input: R, k: u8, m: u8, block_size: usize) -> Result<Self, error::Error> {
+        kmer::check_kmer_size(k, K::MAX_K)?;
+        kmer::check_minimizer_size(k.min(32), m)?;
+
+        Ok(Minimizer {
+            block: Block::new(input, block_size),
+            rolling: Rolling::new(k),
+            m,
+            minimizer: M::default(),
+        })
+    }
+}
+
+impl<R, M, K> Iterator for Minimizer<R, M, K>
+where
+    R: std::io::Read,
+    M: method::Method<K>,
+    K: kmer::Kmer,
+{
+    type Item = Result<(K, u64), error::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let nuc = match self.block.next_byte()? {
+                Ok(nuc) => nuc,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if !self.rolling.push(nuc) {
+                continue;
+            }
+
+            if self.rolling.seen == self.rolling.k as usize {
+                self.minimizer
+                    .init(self.rolling.k, self.m, self.rolling.kmers[0] >> 2);
+            }
+            self.minimizer.add_kmer(self.rolling.kmers[0]);
+
+            return Some(Ok((self.rolling.canonical(), self.minimizer.get_mini().0)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /* project use */
+    use super::*;
+    use crate::test_utils::random_seq;
+    use crate::tokenizer;
+
+    #[test]
+    fn canonical() {
+        let seq = random_seq(200);
+
+        for k in [1, 5, 6, 31, 32] {
+            for block_size in [1, 7, 32, 1000] {
+                assert_eq!(
+                    tokenizer::kmer::Canonical::<u64>::new(&seq, k).collect::<Vec<u64>>(),
+                    Canonical::<_, u64>::try_new(&seq[..], k, block_size)
+                        .unwrap()
+                        .collect::<Result<Vec<u64>, error::Error>>()
+                        .unwrap()
+                );
+            }
+        }
+
+        assert_eq!(
+            tokenizer::kmer::Canonical::<u128>::new_generic(&seq, 41).collect::<Vec<u128>>(),
+            Canonical::<_, u128>::try_new(&seq[..], 41, 13)
+                .unwrap()
+                .collect::<Result<Vec<u128>, error::Error>>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn minimizer() {
+        let seq = random_seq(200);
+
+        for (k, m) in [(11, 7), (12, 6), (32, 15)] {
+            for block_size in [1, 7, 1000] {
+                assert_eq!(
+                    tokenizer::minimizer::Canonical::<method::Random, u64>::new(&seq, k, m)
+                        .collect::<Vec<(u64, u64)>>(),
+                    Minimizer::<_, method::Random, u64>::try_new(&seq[..], k, m, block_size)
+                        .unwrap()
+                        .collect::<Result<Vec<(u64, u64)>, error::Error>>()
+                        .unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn shorter_than_k() {
+        assert!(Canonical::<_, u64>::new(&b"ACTG"[..], 5).next().is_none());
+        assert!(Minimizer::<_, method::Random, u64>::new(&b""[..], 5, 3)
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn io_error() {
+        struct Failing;
+
+        impl std::io::Read for Failing {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("failing"))
+            }
+        }
+
+        assert!(matches!(
+            Canonical::<_, u64>::new(Failing, 5).next(),
+            Some(Err(error::Error::Io { .. }))
+        ));
+    }
+}
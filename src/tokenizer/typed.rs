@@ -0,0 +1,79 @@
+//! An iterator adaptor that produce [typed::Kmer](crate::typed::Kmer) from a kmer tokenizer
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+use crate::kmer;
+use crate::typed;
+
+/// An iterator that wraps a kmer tokenizer and produces kmers with size `K` in their type.
+///
+/// Build it with [Forward::typed](crate::tokenizer::kmer::Forward::typed) or [Canonical::typed](crate::tokenizer::kmer::Canonical::typed).
+///
+/// # Example
+///
+/// ```
+/// use cocktail::tokenizer::kmer::Canonical;
+/// use cocktail::typed::Kmer;
+///
+/// let tokenizer = Canonical::<u64>::typed::<5>(b"GTACTGTGCCCGTGTTACTTAGTAAGCGTGAAAGGTGCGTGTTTCCGAGA");
+///
+/// for kmer in tokenizer {
+///     let kmer: Kmer<5> = kmer;
+///     // ... do what you want ...
+/// }
+/// ```
+pub struct Typed<I, const K: u8> {
+    tokenizer: I,
+}
+
+impl<I, const K: u8> Typed<I, K> {
+    /// Create a new Typed adaptor around tokenizer, tokenizer kmer size must be equal to K
+    pub(crate) fn new(tokenizer: I) -> Self {
+        Typed { tokenizer }
+    }
+}
+
+impl<I, const K: u8> Iterator for Typed<I, K>
+where
+    I: Iterator,
+    I::Item: kmer::Kmer,
+{
+    type Item = typed::Kmer<K, I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tokenizer.next().map(typed::Kmer::from_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::kmer::{Canonical, Forward};
+
+    #[test]
+    fn forward() {
+        assert_eq!(
+            vec![
+                typed::Kmer::<5>::from_seq(b"ACTGA"),
+                typed::Kmer::<5>::from_seq(b"CTGAC"),
+                typed::Kmer::<5>::from_seq(b"TGACT"),
+                typed::Kmer::<5>::from_seq(b"GACTG"),
+            ],
+            Forward::<u64>::typed::<5>(b"ACTGACTG").collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        let kmers = Canonical::<u32>::typed::<5>(b"ACTGACTG").collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![108, 915, 228, 795],
+            kmers.iter().map(|x| x.value()).collect::<Vec<u32>>()
+        );
+        assert!(kmers.iter().all(|x| x.is_canonical()));
+    }
+}
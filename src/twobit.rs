@@ -0,0 +1,600 @@
+//! Read and write UCSC .2bit file.
+//!
+//! .2bit encode nucleotide T=00, C=01, A=10, G=11, sequences are convert in crate encoding (A=00, C=01, T=10, G=11) and store in [PackedSeq].
+//! N and soft mask (lowercase) nucleotides are store in [Record::n_blocks] and [Record::mask_blocks].
+//!
+//! File are read and write in version 0 (32 bits offset), version 1 (64 bits offset) is only read. Both byte order are supported in reading, file are write in little endian.
+//!
+//! # Example
+//!
+//! ```
+//! use cocktail::tokenizer::packed::Canonical;
+//! use cocktail::twobit;
+//!
+//! let records = vec![twobit::Record::from_bytes("chr1", b"GTACTGTGCCCGTGTNNNNNagtaagcgtgAAAGG")];
+//!
+//! let mut file = Vec::new();
+//! twobit::write(&mut file, &records).unwrap();
+//!
+//! let reference = twobit::read(&file[..]).unwrap();
+//! assert_eq!(reference, records);
+//!
+//! for kmer in Canonical::<u64>::new(&reference[0].seq, 5) {
+//!     // ... do what you want ...
+//! }
+//! ```
+
+/* standard use */
+use std::ops::Range;
+
+/* crates use */
+
+/* project use */
+use crate::error;
+use crate::kmer;
+use crate::packed::PackedSeq;
+
+const SIGNATURE: u32 = 0x1A41_2743;
+
+const TRUNCATED: error::Error = error::Error::TwoBitFormat {
+    reason: "unexpected end of file",
+};
+
+/// Maximal capacity preallocate from a count read in file
+const MAX_CAPACITY: usize = 1 << 16;
+
+/// Size of buffer used to read sequences
+const BUFFER_SIZE: usize = 1 << 16;
+
+/// A sequence of a .2bit file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Record {
+    /// Name of sequence
+    pub name: String,
+
+    /// Sequence, nucleotides in n_blocks are store as T
+    pub seq: PackedSeq,
+
+    /// Range of sequence that contains only N
+    pub n_blocks: Vec<Range<usize>>,
+
+    /// Range of sequence that are soft masked
+    pub mask_blocks: Vec<Range<usize>>,
+}
+
+impl Record {
+    /// Build a Record from a DNA sequence, bytes that isn't a nucleotide are store in n_blocks and lowercase in mask_blocks
+    pub fn from_bytes(name: &str, seq: &[u8]) -> Self {
+        let mut packed = PackedSeq::with_capacity(seq.len());
+        for nuc in seq {
+            if kmer::is_nuc(*nuc) {
+                packed.push(*nuc);
+            } else {
+                packed.push(b'T');
+            }
+        }
+
+        Record {
+            name: name.to_string(),
+            seq: packed,
+            n_blocks: blocks(seq, |nuc| !kmer::is_nuc(nuc)),
+            mask_blocks: blocks(seq, |nuc| nuc.is_ascii_lowercase()),
+        }
+    }
+
+    /// Convert Record in a DNA sequence, n_blocks are write as N and mask_blocks in lowercase
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut seq = self.seq.to_bytes();
+
+        for block in self.n_blocks.iter() {
+            seq[block.clone()].fill(b'N');
+        }
+
+        for block in self.mask_blocks.iter() {
+            seq[block.clone()].make_ascii_lowercase();
+        }
+
+        seq
+    }
+}
+
+/// Read all sequences of a .2bit file, input should be buffered
+///
+/// Header and index are read first, then sequences are read in order of their offset, so input is read once and never store whole in memory.
+pub fn read<R>(input: R) -> Result<Vec<Record>, error::Error>
+where
+    R: std::io::Read,
+{
+    let mut input = Input {
+        input,
+        pos: 0,
+        big_endian: false,
+    };
+
+    input.big_endian = match input.u32()? {
+        SIGNATURE => false,
+        s if s == SIGNATURE.swap_bytes() => true,
+        _ => {
+            return Err(error::Error::TwoBitFormat {
+                reason: "bad signature",
+            })
+        }
+    };
+    let version = input.u32()?;
+    if version > 1 {
+        return Err(error::Error::TwoBitFormat {
+            reason: "unsupported version",
+        });
+    }
+    let count = input.u32()? as usize;
+    input.u32()?;
+
+    // count isn't trusted, index grow when entries are read
+    let mut index = Vec::with_capacity(count.min(MAX_CAPACITY));
+    for rank in 0..count {
+        let mut name = vec![0; input.u8()? as usize];
+        input.bytes(&mut name)?;
+        let offset = if version == 0 {
+            input.u32()? as u64
+        } else {
+            input.u64()?
+        };
+
+        index.push((offset, rank, String::from_utf8_lossy(&name).into_owned()));
+    }
+
+    // sequences are read in file order and put back in index order
+    index.sort_unstable_by_key(|(offset, rank, _)| (*offset, *rank));
+    let mut records = Vec::with_capacity(index.len());
+    for (offset, rank, name) in index {
+        input.skip_to(offset)?;
+        records.push((rank, read_record(&mut input, name)?));
+    }
+    records.sort_unstable_by_key(|(rank, _)| *rank);
+
+    Ok(records.into_iter().map(|(_, record)| record).collect())
+}
+
+/// Write sequences in .2bit format
+pub fn write<W>(mut output: W, records: &[Record]) -> Result<(), error::Error>
+where
+    W: std::io::Write,
+{
+    let index_len: usize = records.iter().map(|r| 1 + r.name.len() + 4).sum();
+
+    let mut header = Vec::with_capacity(16 + index_len);
+    header.extend(SIGNATURE.to_le_bytes());
+    header.extend(0u32.to_le_bytes());
+    header.extend(to_u32(records.len())?.to_le_bytes());
+    header.extend(0u32.to_le_bytes());
+
+    let mut offset = 16 + index_len;
+    for record in records {
+        let name_len = u8::try_from(record.name.len()).map_err(|_| error::Error::TwoBitFormat {
+            reason: "sequence name is longer than 255 bytes",
+        })?;
+
+        header.push(name_len);
+        header.extend(record.name.as_bytes());
+        header.extend(to_u32(offset)?.to_le_bytes());
+
+        offset += 4
+            + 4
+            + record.n_blocks.len() * 8
+            + 4
+            + record.mask_blocks.len() * 8
+            + 4
+            + record.seq.len().div_ceil(4);
+    }
+    to_u32(offset)?;
+
+    output.write_all(&header)?;
+    for record in records {
+        write_record(&mut output, record)?;
+    }
+
+    Ok(())
+}
+
+/// Convert 4 nucleotides store in a byte from .2bit encoding to crate encoding and reverse, T <-> A other nucleotides are unchanged
+#[inline(always)]
+fn convert(byte: u8) -> u8 {
+    byte ^ ((!byte & 0x55) << 1)
+}
+
+fn read_record<R>(input: &mut Input<R>, name: String) -> Result<Record, error::Error>
+where
+    R: std::io::Read,
+{
+    let len = input.u32()? as usize;
+    let n_blocks = input.blocks()?;
+    let mask_blocks = input.blocks()?;
+    input.u32()?;
+
+    if n_blocks
+        .iter()
+        .chain(mask_blocks.iter())
+        .any(|block| block.end > len)
+    {
+        return Err(error::Error::TwoBitFormat {
+            reason: "block outside of sequence",
+        });
+    }
+
+    // sequence is read by chunk, len isn't trusted
+    let mut remaining = len.div_ceil(4);
+    let mut words = Vec::with_capacity(remaining.div_ceil(8).min(MAX_CAPACITY));
+    let mut buffer = vec![0; remaining.min(BUFFER_SIZE)];
+    while remaining != 0 {
+        let chunk = &mut buffer[..remaining.min(BUFFER_SIZE)];
+        input.bytes(chunk)?;
+        remaining -= chunk.len();
+
+        words.extend(chunk.chunks(8).map(|bytes| {
+            let mut word = [0; 8];
+            for (dest, byte) in word.iter_mut().zip(bytes) {
+                *dest = convert(*byte);
+            }
+            u64::from_be_bytes(word)
+        }));
+    }
+
+    Ok(Record {
+        name,
+        seq: PackedSeq::from_words(words, len),
+        n_blocks,
+        mask_blocks,
+    })
+}
+
+fn write_record<W>(output: &mut W, record: &Record) -> Result<(), error::Error>
+where
+    W: std::io::Write,
+{
+    output.write_all(&to_u32(record.seq.len())?.to_le_bytes())?;
+
+    for blocks in [&record.n_blocks, &record.mask_blocks] {
+        output.write_all(&to_u32(blocks.len())?.to_le_bytes())?;
+        for block in blocks.iter() {
+            output.write_all(&to_u32(block.start)?.to_le_bytes())?;
+        }
+        for block in blocks.iter() {
+            output.write_all(&to_u32(block.end - block.start)?.to_le_bytes())?;
+        }
+    }
+    output.write_all(&0u32.to_le_bytes())?;
+
+    let mut packed = Vec::with_capacity(record.seq.len().div_ceil(4));
+    for word in record.seq.words() {
+        packed.extend(word.to_be_bytes().map(convert));
+    }
+    packed.truncate(record.seq.len().div_ceil(4));
+
+    // Padding nucleotides of last byte are write as T
+    let padding = (packed.len() * 4 - record.seq.len()) * 2;
+    if let Some(last) = packed.last_mut() {
+        *last &= u8::MAX.checked_shl(padding as u32).unwrap_or(0);
+    }
+
+    output.write_all(&packed)?;
+
+    Ok(())
+}
+
+fn to_u32(value: usize) -> Result<u32, error::Error> {
+    u32::try_from(value).map_err(|_| error::Error::TwoBitFormat {
+        reason: "value doesn't fit in 32 bits",
+    })
+}
+
+/// Build list of range where pred is true
+fn blocks<P>(seq: &[u8], pred: P) -> Vec<Range<usize>>
+where
+    P: Fn(u8) -> bool,
+{
+    let mut blocks: Vec<Range<usize>> = Vec::new();
+
+    for (pos, nuc) in seq.iter().enumerate() {
+        if !pred(*nuc) {
+            continue;
+        }
+
+        match blocks.last_mut() {
+            Some(block) if block.end == pos => block.end += 1,
+            _ => blocks.push(pos..pos + 1),
+        }
+    }
+
+    blocks
+}
+
+struct Input<R> {
+    input: R,
+    pos: u64,
+    big_endian: bool,
+}
+
+impl<R> Input<R>
+where
+    R: std::io::Read,
+{
+    fn bytes(&mut self, buffer: &mut [u8]) -> Result<(), error::Error> {
+        error::read_exact(&mut self.input, buffer, TRUNCATED)?;
+        self.pos += buffer.len() as u64;
+
+        Ok(())
+    }
+
+    /// Move forward to offset, offset can't be before current position
+    fn skip_to(&mut self, offset: u64) -> Result<(), error::Error> {
+        if offset < self.pos {
+            return Err(error::Error::TwoBitFormat {
+                reason: "sequence offset inside header or previous sequence",
+            });
+        }
+
+        let len = offset - self.pos;
+        let skipped = std::io::copy(
+            &mut std::io::Read::take(&mut self.input, len),
+            &mut std::io::sink(),
+        )?;
+        if skipped != len {
+            return Err(error::Error::TwoBitFormat {
+                reason: "sequence offset outside of file",
+            });
+        }
+        self.pos = offset;
+
+        Ok(())
+    }
+
+    fn u8(&mut self) -> Result<u8, error::Error> {
+        let mut bytes = [0; 1];
+        self.bytes(&mut bytes)?;
+
+        Ok(bytes[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, error::Error> {
+        let mut bytes = [0; 4];
+        self.bytes(&mut bytes)?;
+
+        if self.big_endian {
+            Ok(u32::from_be_bytes(bytes))
+        } else {
+            Ok(u32::from_le_bytes(bytes))
+        }
+    }
+
+    fn u64(&mut self) -> Result<u64, error::Error> {
+        let mut bytes = [0; 8];
+        self.bytes(&mut bytes)?;
+
+        if self.big_endian {
+            Ok(u64::from_be_bytes(bytes))
+        } else {
+            Ok(u64::from_le_bytes(bytes))
+        }
+    }
+
+    fn blocks(&mut self) -> Result<Vec<Range<usize>>, error::Error> {
+        // count isn't trusted, blocks grow when they are read
+        let count = self.u32()? as usize;
+
+        let mut starts = Vec::with_capacity(count.min(MAX_CAPACITY));
+        for _ in 0..count {
+            starts.push(self.u32()? as usize);
+        }
+
+        let mut blocks = Vec::with_capacity(starts.len());
+        for start in starts {
+            let size = self.u32()? as usize;
+            blocks.push(start.checked_add(size).map(|end| start..end).ok_or(
+                error::Error::TwoBitFormat {
+                    reason: "block outside of sequence",
+                },
+            )?);
+        }
+
+        Ok(blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // .2bit file, write by hand from format specification, with sequences:
+    // >seq1
+    // ACGTNNacgtA
+    // >s2
+    // TTGCA
+    const UCSC: &[u8] = &[
+        0x43, 0x27, 0x41, 0x1a, // signature
+        0x00, 0x00, 0x00, 0x00, // version
+        0x02, 0x00, 0x00, 0x00, // sequence count
+        0x00, 0x00, 0x00, 0x00, // reserved
+        0x04, b's', b'e', b'q', b'1', 0x20, 0x00, 0x00, 0x00, // seq1 index
+        0x02, b's', b'2', 0x43, 0x00, 0x00, 0x00, // s2 index
+        0x0b, 0x00, 0x00, 0x00, // seq1 size
+        0x01, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, // n blocks
+        0x01, 0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, // mask blocks
+        0x00, 0x00, 0x00, 0x00, // reserved
+        0x9c, 0x09, 0xc8, // ACGT TTAC GTA-
+        0x05, 0x00, 0x00, 0x00, // s2 size
+        0x00, 0x00, 0x00, 0x00, // n blocks
+        0x00, 0x00, 0x00, 0x00, // mask blocks
+        0x00, 0x00, 0x00, 0x00, // reserved
+        0x0d, 0x80, // TTGC A---
+    ];
+
+    #[test]
+    fn convert_() {
+        // A C T G in .2bit encoding -> A C T G in crate encoding
+        assert_eq!(convert(0b10_01_00_11), 0b00_01_10_11);
+        assert_eq!(convert(0b00_01_10_11), 0b10_01_00_11);
+    }
+
+    #[test]
+    fn record() {
+        let record = Record::from_bytes("seq1", b"ACGTNNacgtA");
+
+        assert_eq!(record.n_blocks, vec![4..6]);
+        assert_eq!(record.mask_blocks, vec![6..10]);
+        assert_eq!(record.seq.to_bytes(), b"ACGTTTACGTA".to_vec());
+        assert_eq!(record.to_bytes(), b"ACGTNNacgtA".to_vec());
+    }
+
+    #[test]
+    fn read_ucsc() {
+        let records = read(UCSC).unwrap();
+
+        assert_eq!(
+            records,
+            vec![
+                Record::from_bytes("seq1", b"ACGTNNacgtA"),
+                Record::from_bytes("s2", b"TTGCA")
+            ]
+        );
+    }
+
+    #[test]
+    fn write_ucsc() {
+        let mut output = Vec::new();
+        write(
+            &mut output,
+            &[
+                Record::from_bytes("seq1", b"ACGTNNacgtA"),
+                Record::from_bytes("s2", b"TTGCA"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(output, UCSC.to_vec());
+    }
+
+    #[test]
+    fn big_endian() {
+        let mut data = UCSC.to_vec();
+        for range in [
+            0..4,
+            4..8,
+            8..12,
+            21..25,
+            28..32,
+            32..36,
+            36..40,
+            40..44,
+            44..48,
+            48..52,
+            52..56,
+            56..60,
+            67..71,
+        ] {
+            data[range].reverse();
+        }
+
+        assert_eq!(&data[..4], &SIGNATURE.to_be_bytes());
+        assert_eq!(read(&data[..]).unwrap(), read(UCSC).unwrap());
+    }
+
+    #[test]
+    fn round_trip() {
+        let records = vec![
+            Record::from_bytes("empty", b""),
+            Record::from_bytes("long", &b"ACTGGTACnnnnnNNNNNGTAACTtgatcgAA".repeat(10)),
+        ];
+
+        let mut output = Vec::new();
+        write(&mut output, &records).unwrap();
+
+        assert_eq!(read(&output[..]).unwrap(), records);
+    }
+
+    #[test]
+    fn offset_order() {
+        // s2 is store before seq1
+        let mut file = UCSC.to_vec();
+        file[21..25].copy_from_slice(&67u32.to_le_bytes());
+        file[28..32].copy_from_slice(&32u32.to_le_bytes());
+
+        assert_eq!(
+            read(&file[..]).unwrap(),
+            vec![
+                Record::from_bytes("seq1", b"TTGCA"),
+                Record::from_bytes("s2", b"ACGTNNacgtA")
+            ]
+        );
+
+        file[21..25].copy_from_slice(&32u32.to_le_bytes());
+        assert_eq!(
+            read(&file[..]),
+            Err(error::Error::TwoBitFormat {
+                reason: "sequence offset inside header or previous sequence"
+            })
+        );
+
+        file[28..32].copy_from_slice(&8u32.to_le_bytes());
+        assert_eq!(
+            read(&file[..]),
+            Err(error::Error::TwoBitFormat {
+                reason: "sequence offset inside header or previous sequence"
+            })
+        );
+    }
+
+    #[test]
+    fn error() {
+        assert_eq!(
+            read(&UCSC[..40]),
+            Err(error::Error::TwoBitFormat {
+                reason: "unexpected end of file"
+            })
+        );
+
+        // a wrong count didn't allocate memory before index is read
+        let mut file = UCSC.to_vec();
+        file[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            read(&file[..]),
+            Err(error::Error::TwoBitFormat {
+                reason: "unexpected end of file"
+            })
+        );
+
+        let mut file = UCSC.to_vec();
+        file[21..25].copy_from_slice(&1000u32.to_le_bytes());
+        assert_eq!(
+            read(&file[..]),
+            Err(error::Error::TwoBitFormat {
+                reason: "sequence offset outside of file"
+            })
+        );
+
+        // a wrong sequence size didn't allocate memory before sequence is read
+        let mut file = UCSC.to_vec();
+        file[32..36].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            read(&file[..]),
+            Err(error::Error::TwoBitFormat {
+                reason: "unexpected end of file"
+            })
+        );
+
+        let mut file = UCSC.to_vec();
+        file[36..40].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            read(&file[..]),
+            Err(error::Error::TwoBitFormat {
+                reason: "unexpected end of file"
+            })
+        );
+
+        assert_eq!(
+            read(&b"not a 2bit file"[..]),
+            Err(error::Error::TwoBitFormat {
+                reason: "bad signature"
+            })
+        );
+    }
+}
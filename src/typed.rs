@@ -0,0 +1,220 @@
+//! A kmer in 2 bit representation that know is size.
+//!
+//! [Kmer] carry kmer size `K` in is type, so kmers of different size can't be mixed and function of [kmer](crate::kmer) module didn't need `k` parameter.
+//! Storage type `T` is any [kmer::Kmer](crate::kmer::Kmer), `u64` by default.
+//!
+//! # Example
+//!
+//! ```
+//! use cocktail::typed::Kmer;
+//!
+//! let kmer: Kmer<5> = "GCCTA".parse().unwrap();
+//!
+//! assert_eq!(kmer.revcomp().to_string(), "TAGGC");
+//! assert_eq!(kmer.canonical().to_string(), "TAGGC");
+//! assert_eq!(kmer.hash(), 0b100011110);
+//! ```
+
+/* standard use */
+
+/* crates use */
+
+/* project use */
+use crate::error;
+use crate::kmer;
+
+/// A kmer of size `K` store in a `T`
+///
+/// `K` must be in 1..=`T::MAX_K` else build failed:
+///
+/// ```compile_fail
+/// let kmer = cocktail::typed::Kmer::<33, u64>::from_seq(b"ACTG");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Kmer<const K: u8, T = u64>(T);
+
+impl<const K: u8, T> Kmer<K, T>
+where
+    T: kmer::Kmer,
+{
+    const VALID_SIZE: () = assert!(K > 0 && K <= T::MAX_K, "kmer size not supported by type");
+
+    /// Build a kmer from a 2 bit representation, bits upper than 2 * K are ignored
+    #[inline(always)]
+    pub fn from_bits(value: T) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let _ = Self::VALID_SIZE;
+
+        Kmer(value & T::kmer_mask(K))
+    }
+
+    /// Build a kmer from the last K nucleotide of subseq
+    #[inline(always)]
+    pub fn from_seq(subseq: &[u8]) -> Self {
+        Self::from_bits(T::seq2bit(subseq))
+    }
+
+    /// Return the 2 bit representation of kmer
+    #[inline(always)]
+    pub fn value(self) -> T {
+        self.0
+    }
+
+    /// Return the kmer size
+    #[inline(always)]
+    pub fn k(self) -> u8 {
+        K
+    }
+
+    /// Return the reverse complement of kmer
+    #[inline(always)]
+    pub fn revcomp(self) -> Self {
+        Kmer(self.0.revcomp(K))
+    }
+
+    /// Return the canonical form of kmer, selected by parity if K is odd, smallest value if K is even
+    #[inline(always)]
+    pub fn canonical(self) -> Self {
+        Kmer(self.0.canonical_any(K))
+    }
+
+    /// Return true if kmer is in canonical form
+    #[inline(always)]
+    pub fn is_canonical(self) -> bool {
+        self.canonical() == self
+    }
+
+    /// Return the canonical kmer without the rightest bit if K is odd, the canonical kmer if K is even
+    #[inline(always)]
+    pub fn hash(self) -> T {
+        let cano = self.0.canonical_any(K);
+
+        if K % 2 == 1 {
+            cano.remove_first_bit()
+        } else {
+            cano
+        }
+    }
+
+    /// Build the canonical kmer from a hash produce by [hash](Kmer::hash)
+    #[inline(always)]
+    pub fn from_hash(hash: T) -> Self {
+        Self::from_bits(hash.hash2kmer(K))
+    }
+
+    /// Return the sequence of kmer
+    #[inline(always)]
+    pub fn to_seq(self) -> Vec<u8> {
+        self.0.kmer2seq(K)
+    }
+}
+
+impl<const K: u8, T> std::fmt::Display for Kmer<K, T>
+where
+    T: kmer::Kmer,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut buffer = [0; 64];
+        self.0.kmer2seq_into(K, &mut buffer);
+
+        // buffer contains only A, C, T or G
+        f.write_str(unsafe { std::str::from_utf8_unchecked(&buffer[..K as usize]) })
+    }
+}
+
+impl<const K: u8, T> std::str::FromStr for Kmer<K, T>
+where
+    T: kmer::Kmer,
+{
+    type Err = error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != K as usize {
+            return Err(error::Error::SequenceLength {
+                expected: K as usize,
+                found: s.len(),
+            });
+        }
+
+        T::seq2bit_checked(s.as_bytes()).map(Self::from_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_seq() {
+        // TAGGC -> 1000111101
+        assert_eq!(Kmer::<5>::from_seq(b"TAGGC").value(), 0b1000111101);
+        assert_eq!(Kmer::<3>::from_seq(b"TAGGC").value(), 0b111101);
+        assert_eq!(Kmer::<3, u8>::from_bits(0b10111101).value(), 0b111101);
+        assert_eq!(Kmer::<5, u16>::from_seq(b"TAGGC").k(), 5);
+    }
+
+    #[test]
+    fn revcomp_canonical_hash() {
+        let forward = Kmer::<5>::from_seq(b"TAGGC");
+        let reverse = Kmer::<5>::from_seq(b"GCCTA");
+
+        assert_eq!(forward.revcomp(), reverse);
+        assert_eq!(reverse.canonical(), forward);
+        assert!(forward.is_canonical());
+        assert!(!reverse.is_canonical());
+        assert_eq!(forward.hash(), reverse.hash());
+        assert_eq!(reverse.hash(), crate::kmer::hash(b"GCCTA", 5));
+    }
+
+    #[test]
+    fn even_k() {
+        let forward = Kmer::<4>::from_seq(b"TAGG");
+        let reverse = Kmer::<4>::from_seq(b"CCTA");
+
+        assert_eq!(forward.canonical(), reverse);
+        assert_eq!(reverse.canonical(), reverse);
+        assert!(reverse.is_canonical());
+        assert!(!forward.is_canonical());
+        assert_eq!(forward.hash(), crate::kmer::hash_min(b"TAGG", 4));
+        assert_eq!(forward.hash(), reverse.hash());
+    }
+
+    #[test]
+    fn from_hash() {
+        let kmer = Kmer::<5>::from_seq(b"GCCTA");
+        assert_eq!(Kmer::<5>::from_hash(kmer.hash()), kmer.canonical());
+
+        let kmer = Kmer::<4, u8>::from_seq(b"TAGG");
+        assert_eq!(Kmer::<4, u8>::from_hash(kmer.hash()), kmer.canonical());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Kmer::<5>::from_seq(b"tagGC").to_string(), "TAGGC");
+        assert_eq!(Kmer::<5, u128>::from_seq(b"TAGGC").to_string(), "TAGGC");
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!(
+            "TAGGC".parse::<Kmer<5>>(),
+            Ok(Kmer::<5>::from_seq(b"TAGGC"))
+        );
+
+        assert_eq!(
+            "TAGG".parse::<Kmer<5>>(),
+            Err(error::Error::SequenceLength {
+                expected: 5,
+                found: 4
+            })
+        );
+
+        assert_eq!(
+            "TANGC".parse::<Kmer<5>>(),
+            Err(error::Error::Nucleotide {
+                byte: b'N',
+                position: 2
+            })
+        );
+    }
+}